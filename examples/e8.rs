@@ -15,6 +15,7 @@ use project::{
 	helpers::geometry_generators,
 	math::{Vector, Vector3},
 	render,
+	resources::BackgroundLoader,
 	specs::*,
 };
 
@@ -92,6 +93,7 @@ fn main() {
 	let mut render_system =
 		render::open_gl::system_render::RenderSystem::new(&mut world, true, true, true);
 	let mut system_transform = SystemTransform::new();
+	let loader = BackgroundLoader::new();
 
 	let up = Vector3::new(0.0, 1.0, 0.0);
 	let center = Vector3::new_zero();
@@ -119,12 +121,14 @@ fn main() {
 
 	world.add_child(root, e_cam);
 
+	let mut texture_loads = Vec::new();
 	emojis.iter().for_each(|item| {
 		let mut pos = Vector3::random();
 		pos.multiply_scalar(10.0);
 		pos.sub_scalar(5.0);
 
 		let texture = SharedTexture2D::new_from_path(item);
+		texture_loads.push((*item, loader.load_texture(&texture)));
 		let mut mat = Material::new_mesh_standard();
 		mat.set_uniform(UniformName::MapColor, texture);
 		mat.set_uniform(UniformName::Alpha, 1.0);
@@ -234,6 +238,15 @@ fn main() {
 	while running {
 		// frame_count += 1;
 
+		texture_loads.retain(|(path, handle)| match handle.take() {
+			Some(Err(err)) => {
+				println!("cant load {}: {}", path, err);
+				false
+			}
+			Some(Ok(_)) => false,
+			None => true,
+		});
+
 		{
 			let windowed_context = &render_system.windowed_context;
 			use self::glutin::WindowEvent::*;
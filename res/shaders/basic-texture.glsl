@@ -5,7 +5,8 @@ layout (location = 1) in vec3 aColor;
 layout (location = 2) in vec2 aUv;
 
 uniform mat4 matrix_model;
-uniform mat4 matrix_view;
+#include <snippet-camera>
+#include <snippet-instancing>
 
 out vec4 v_color;
 out vec2 v_uv;
@@ -13,7 +14,7 @@ out vec2 v_uv;
 void main() {
 	v_color = vec4(aColor.xyz, 1.0);
 	v_uv = aUv;
-	gl_Position = matrix_view * matrix_model * vec4(aPos.xyz, 1.0);
+	gl_Position = matrix_view * MODEL_MATRIX * vec4(aPos.xyz, 1.0);
 }
 
 
@@ -2,11 +2,12 @@
 
 layout (location = 0) in vec3 aPos;
 uniform mat4 matrix_model;
-uniform mat4 matrix_view;
+#include <snippet-camera>
+#include <snippet-instancing>
 out vec4 v_color;
 
 void main() {
-	gl_Position = matrix_view * matrix_model * vec4(aPos.xyz, 1.0);
+	gl_Position = matrix_view * MODEL_MATRIX * vec4(aPos.xyz, 1.0);
 }
 
 
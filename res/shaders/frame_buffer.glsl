@@ -3,7 +3,7 @@ in vec3 B_Pos;
 
 
 uniform mat4 matrix_model;
-uniform mat4 matrix_view;
+#include <snippet-camera>
 uniform mat3 matrix_normal;
 
 out vec3 v_pos;
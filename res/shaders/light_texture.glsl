@@ -1,7 +1,7 @@
 #<vertex>
 
 uniform mat4 matrix_model;
-uniform mat4 matrix_view;
+#include <snippet-camera>
 uniform mat3 matrix_normal;
 uniform vec3 position_light;
 
@@ -3,7 +3,7 @@ layout (location = 0) in vec3 aPos;
 layout (location = 1) in vec3 aNormal;
 layout (location = 2) in vec2 aUv;
 uniform mat4 matrix_model;
-uniform mat4 matrix_view;
+#include <snippet-camera>
 uniform mat3 matrix_normal;
 
 out vec3 e;
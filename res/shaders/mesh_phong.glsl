@@ -4,16 +4,17 @@ layout (location = 1) in vec3 aNormal;
 layout (location = 2) in vec2 aTexCoords;
 
 uniform mat4 matrix_model;
-uniform mat4 matrix_view;
+#include <snippet-camera>
 uniform mat3 matrix_normal;
+#include <snippet-instancing>
 
 out vec3 v_pos;
 out vec3 v_normal;
 out vec2 v_uv;
 
 void main() {
-	v_pos = vec3(matrix_model * vec4(aPos, 1.0));
-	v_normal = matrix_normal * aNormal;
+	v_pos = vec3(MODEL_MATRIX * vec4(aPos, 1.0));
+	v_normal = NORMAL_MATRIX * aNormal;
 	v_uv = aTexCoords;
 
 	gl_Position = matrix_view * vec4(v_pos, 1.0);
@@ -48,7 +49,7 @@ uniform float shininess;
 
 // uniform vec3 viewPos;
 // uniform Material material;
-uniform PointLight pointLights[ NUM_POINT_LIGHTS ];
+#include <snippet-lights>
 
 void main()
 {
@@ -10,11 +10,21 @@ in vec4 B_Color;
 #ifdef VERTEX_COLOR_0_VEC3
 in vec3 B_Color;
 #endif
+#ifdef VERTEX_TANGENT_VEC4
+in vec4 B_Tangent;
+#endif
+#ifdef INSTANCE_COLOR_0_VEC4
+layout (location = INSTANCE_COLOR_0_VEC4) in vec4 I_Color;
+#endif
+#ifdef INSTANCE_COLOR_0_VEC3
+layout (location = INSTANCE_COLOR_0_VEC3) in vec3 I_Color;
+#endif
 
 
 uniform mat4 matrix_model;
-uniform mat4 matrix_view;
+#include <snippet-camera>
 uniform mat3 matrix_normal;
+#include <snippet-instancing>
 
 out vec3 v_pos;
 out vec3 v_normal;
@@ -27,11 +37,17 @@ out vec4 v_color;
 #ifdef VERTEX_COLOR_0_VEC3
 out vec3 v_color;
 #endif
+#ifdef VERTEX_TANGENT_VEC4
+out vec4 v_tangent;
+#endif
+#if defined INSTANCE_COLOR_0_VEC4 || defined INSTANCE_COLOR_0_VEC3
+out vec3 v_instance_color;
+#endif
 
 
 void main() {
-	v_pos = vec3(matrix_model * vec4(B_Pos, 1.0));
-	v_normal = matrix_normal * B_Normal;
+	v_pos = vec3(MODEL_MATRIX * vec4(B_Pos, 1.0));
+	v_normal = NORMAL_MATRIX * B_Normal;
 
 	#ifdef VERTEX_UV_0_VEC2
 	v_uv = B_uv;
@@ -39,6 +55,12 @@ void main() {
 	#if defined VERTEX_COLOR_0_VEC4 || defined VERTEX_COLOR_0_VEC3
 	v_color = B_Color;
 	#endif
+	#ifdef VERTEX_TANGENT_VEC4
+	v_tangent = vec4(mat3(MODEL_MATRIX) * B_Tangent.xyz, B_Tangent.w);
+	#endif
+	#if defined INSTANCE_COLOR_0_VEC4 || defined INSTANCE_COLOR_0_VEC3
+	v_instance_color = I_Color.xyz;
+	#endif
 
 	gl_Position = matrix_view * vec4(v_pos, 1.0);
 }
@@ -68,6 +90,12 @@ in vec4 v_color;
 #ifdef VERTEX_COLOR_0_VEC3
 in vec3 v_color;
 #endif
+#ifdef VERTEX_TANGENT_VEC4
+in vec4 v_tangent;
+#endif
+#if defined INSTANCE_COLOR_0_VEC4 || defined INSTANCE_COLOR_0_VEC3
+in vec3 v_instance_color;
+#endif
 
 uniform vec3 color;
 uniform vec3 specular;
@@ -82,12 +110,7 @@ uniform vec3 ambient_light;
 // uniform vec3 viewPos;
 // uniform Material material;
 
-#if ( NUM_POINT_LIGHTS > 0 )
-	uniform PointLight pointLights[ NUM_POINT_LIGHTS ];
-#endif
-#if ( NUM_DIR_LIGHTS > 0 )
-	uniform DirectionalLight directionalLights[ NUM_DIR_LIGHTS ];
-#endif
+#include <snippet-lights>
 
 
 
@@ -130,6 +153,10 @@ void main()
 	#endif
 #endif
 
+#if defined INSTANCE_COLOR_0_VEC4 || defined INSTANCE_COLOR_0_VEC3
+	diffuseColor *= v_instance_color;
+#endif
+
 
 #if defined SHADELESS
 	FragColor = vec4(diffuseColor, fragmentAalpha);
@@ -152,7 +179,12 @@ void main()
 	geometry.normal = normalize( v_normal );
 	geometry.viewDir = normalize( -v_pos );
 
-#if defined VERTEX_UV_0_VEC2 && defined MAP_NORMAL
+#if defined VERTEX_UV_0_VEC2 && defined MAP_NORMAL && defined VERTEX_TANGENT_VEC4
+vec3 tangent = normalize(v_tangent.xyz - geometry.normal * dot(geometry.normal, v_tangent.xyz));
+vec3 bitangent = cross(geometry.normal, tangent) * v_tangent.w;
+vec3 map_n = texture(map_normal, v_uv).xyz * 2.0 - 1.0;
+geometry.normal = normalize(mat3(tangent, bitangent, geometry.normal) * map_n);
+#elif defined VERTEX_UV_0_VEC2 && defined MAP_NORMAL
 // geometry.normal = -(texture2D( map_normal, v_uv ).xyz * 2.0 - 1.0);
 // TODO: fix
 geometry.normal = normalize(geometry.normal * vec3(texture(map_normal, v_uv)));
@@ -0,0 +1,7 @@
+#pragma once
+
+// per frame data, shared by all programs (see gl_uniform_buffer.rs)
+layout (std140) uniform Camera {
+	mat4 matrix_view;
+	float time;
+};
@@ -1,3 +1,4 @@
+#pragma once
 
 struct IncidentLight {
 	vec3 color;
@@ -1,3 +1,4 @@
+#pragma once
 
 #define PI 3.14159265359
 #define PI2 6.28318530718
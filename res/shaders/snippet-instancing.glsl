@@ -0,0 +1,12 @@
+#pragma once
+
+// per instance transform from `Instances`, applied in model space
+#ifdef INSTANCED
+layout (location = INSTANCE_MATRIX) in mat4 I_Matrix;
+#define MODEL_MATRIX (matrix_model * I_Matrix)
+// inverse transpose keeps normals right for not uniformly scaled instances
+#define NORMAL_MATRIX (matrix_normal * transpose(inverse(mat3(I_Matrix))))
+#else
+#define MODEL_MATRIX matrix_model
+#define NORMAL_MATRIX matrix_normal
+#endif
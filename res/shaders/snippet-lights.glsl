@@ -0,0 +1,15 @@
+#pragma once
+#include <snippet-common-lighting>
+
+// per frame data, shared by all programs (see gl_uniform_buffer.rs)
+#if ( NUM_POINT_LIGHTS > 0 )
+layout (std140) uniform PointLights {
+	PointLight pointLights[ NUM_POINT_LIGHTS ];
+};
+#endif
+
+#if ( NUM_DIR_LIGHTS > 0 )
+layout (std140) uniform DirectionalLights {
+	DirectionalLight directionalLights[ NUM_DIR_LIGHTS ];
+};
+#endif
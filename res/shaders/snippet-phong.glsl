@@ -1,3 +1,4 @@
+#pragma once
 
 struct BlinnPhongMaterial {
 	vec3	diffuseColor;
@@ -1,3 +1,4 @@
+#pragma once
 
 
 #define MAXIMUM_SPECULAR_COEFFICIENT 0.16
@@ -3,9 +3,8 @@ layout (location = 0) in vec3 aPos;
 layout (location = 1) in vec3 aNormal;
 layout (location = 2) in vec2 aUv;
 uniform mat4 matrix_model;
-uniform mat4 matrix_view;
+#include <snippet-camera>
 uniform mat3 matrix_normal;
-uniform float time;
 
 out vec3 n;
 out vec3 p;
@@ -31,7 +30,7 @@ void main() {
 
 #<fragment>
 uniform sampler2D map_color;
-uniform float time;
+#include <snippet-camera>
 
 in vec3 n;
 in vec3 p;
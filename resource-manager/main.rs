@@ -1,46 +1,88 @@
 extern crate clap;
 extern crate colored;
-extern crate tar;
 extern crate pathdiff;
+extern crate project;
 
-mod lib;
-
-use clap::{App, Arg, SubCommand};
-use std::ffi::OsString;
-#[macro_use]
+use clap::{App, Arg, ArgMatches, SubCommand};
 use colored::*;
 
-use std::collections::HashMap;
-use std::fs::File;
-// use std::io::prelude::*;
-use lib::*;
-use std::path::PathBuf;
-use tar::Builder;
 use pathdiff::diff_paths;
-// use tar::Builder;
+use project::core::{CompressedFormat, MeshBake, TextureBake};
+use project::resources::*;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+enum Level {
+	Error,
+	Warn,
+	Info,
+	Debug,
+}
 
-// use std::env;
-// use std::fs;
+struct Log {
+	level: Level,
+}
+
+impl Log {
+	fn error(&self, message: &str) {
+		eprintln!("{} {}", "error:".bright_red(), message);
+	}
+
+	fn warn(&self, message: &str) {
+		if self.level >= Level::Warn {
+			eprintln!("{} {}", "warning:".bright_yellow(), message);
+		}
+	}
+
+	fn info(&self, message: &str) {
+		if self.level >= Level::Info {
+			println!("{}", message);
+		}
+	}
+
+	fn debug(&self, message: &str) {
+		if self.level >= Level::Debug {
+			println!("{}", message.dimmed());
+		}
+	}
+}
+
+struct BuildOptions {
+	out_dir: PathBuf,
+	single_file: bool,
+	compression: Compression,
+	// for packages that don't specify it
+	textures: Option<TextureBake>,
+	meshes: Option<MeshBake>,
+	dry_run: bool,
+	force: bool,
+}
 
 fn main() {
-	let matches = App::new("My Super Program")
+	let manifest_arg = Arg::with_name("manifest")
+		.required(true)
+		.index(1)
+		.help("package list written by build (res.json)");
+
+	let matches = App::new("resource-manager")
 		.version(env!("CARGO_PKG_VERSION"))
 		.author("Stepan K. <shtefanrpg@gmail.com>")
 		.about("resource manager")
-		//   .arg(Arg::with_name("config")
-		//        .short("c")
-		//        .long("config")
-		//        .value_name("FILE")
-		//        .help("Sets a custom config file")
-		//        .takes_value(true))
-		//   .arg(Arg::with_name("INPUT")
-		//        .help("Sets the input file to use")
-		//        .required(true)
-		//        .index(1))
-		//   .arg(Arg::with_name("v")
-		//        .short("v")
-		//        .multiple(true)
-		//        .help("Sets the level of verbosity"))
+		.arg(
+			Arg::with_name("verbose")
+				.short("v")
+				.multiple(true)
+				.global(true)
+				.help("print debug information verbosely"),
+		)
+		.arg(
+			Arg::with_name("quiet")
+				.short("q")
+				.global(true)
+				.help("print errors only"),
+		)
 		.subcommand(
 			SubCommand::with_name("build")
 				.about("build resources")
@@ -50,7 +92,7 @@ fn main() {
 						.multiple(true)
 						.required(true)
 						.takes_value(true)
-						.help("print debug information verbosely"),
+						.help("package lists to build"),
 				)
 				.arg(
 					Arg::with_name("output_dir")
@@ -65,82 +107,201 @@ fn main() {
 						.short("-s")
 						.required(false)
 						.help("force to create one file \"res.tar\""),
+				)
+				.arg(
+					Arg::with_name("compression")
+						.short("-c")
+						.required(false)
+						.takes_value(true)
+						.possible_values(&["none", "gzip", "zstd"])
+						.default_value("none")
+						.help("compression for packages that don't specify it"),
+				)
+				.arg(
+					Arg::with_name("mipmaps")
+						.long("mipmaps")
+						.help("bake mip chains of textures for packages that don't specify it"),
+				)
+				.arg(
+					Arg::with_name("texture_format")
+						.short("t")
+						.long("texture-format")
+						.takes_value(true)
+						.possible_values(&["none", "bc1", "bc3", "etc2_rgb", "etc2_rgba"])
+						.default_value("none")
+						.help("encode textures for packages that don't specify it"),
+				)
+				.arg(
+					Arg::with_name("optimize_meshes")
+						.long("optimize-meshes")
+						.help("optimize obj meshes for vertex cache, overdraw and vertex fetch for packages that don't specify it"),
+				)
+				.arg(
+					Arg::with_name("force")
+						.short("f")
+						.long("force")
+						.help("rebuild packages that are up to date"),
+				)
+				.arg(
+					Arg::with_name("dry_run")
+						.long("dry-run")
+						.help("print what would be built without writing files"),
+				),
+		)
+		.subcommand(
+			SubCommand::with_name("list")
+				.about("list packages and resources")
+				.arg(manifest_arg.clone()),
+		)
+		.subcommand(
+			SubCommand::with_name("verify")
+				.about("check package archives against the manifest")
+				.arg(manifest_arg.clone()),
+		)
+		.subcommand(
+			SubCommand::with_name("unpack")
+				.about("extract resources from package archives")
+				.arg(manifest_arg)
+				.arg(
+					Arg::with_name("output_dir")
+						.short("-o")
+						.takes_value(true)
+						.default_value("./")
+						.help("Specify output dir"),
+				)
+				.arg(
+					Arg::with_name("package")
+						.short("p")
+						.multiple(true)
+						.takes_value(true)
+						.help("packages to unpack, all by default"),
+				)
+				.arg(
+					Arg::with_name("dry_run")
+						.long("dry-run")
+						.help("print what would be unpacked without writing files"),
 				),
 		)
 		.get_matches();
 
-	println!("{:?}", matches);
-	println!("===================");
+	let log = Log {
+		level: match (matches.is_present("quiet"), matches.occurrences_of("verbose")) {
+			(true, _) => Level::Error,
+			(false, 0) => Level::Info,
+			_ => Level::Debug,
+		},
+	};
 
-	match matches.subcommand {
-		None => {
-			println!("{}", matches.usage.unwrap());
-			return;
+	let result = match matches.subcommand() {
+		("build", Some(command)) => run_build(&log, command),
+		("list", Some(command)) => list(&manifest_path(command)),
+		("verify", Some(command)) => verify(&log, &manifest_path(command)),
+		("unpack", Some(command)) => {
+			let packages = command
+				.values_of("package")
+				.map(|values| values.map(|e| e.to_string()).collect());
+			unpack(
+				&log,
+				&manifest_path(command),
+				&PathBuf::from(command.value_of("output_dir").unwrap()),
+				packages,
+				command.is_present("dry_run"),
+			)
 		}
-		Some(command) => match &command.name[..] {
-			"build" => {
-				println!("************");
-				println!("{:?}", command.matches);
-				println!("************");
-
-				let vals = &command.matches.args.get("list").unwrap().vals;
-				let out_dir = &command.matches.args.get("output_dir").unwrap().vals[0];
-				let single_file = command.matches.args.get("single_file").is_some();
+		_ => {
+			eprintln!("{}", matches.usage());
+			std::process::exit(2);
+		}
+	};
 
-				build(vals, out_dir, single_file);
-			}
-			_ => unimplemented!(),
-		}, // Some(command) => println!(" ==> {:?}", command)
+	if let Err(err) = result {
+		log.error(&err);
+		std::process::exit(1);
 	}
 }
 
-fn build(files: &Vec<OsString>, out_dir: &OsString, single_file: bool) {
-	let dir = std::env::current_dir().unwrap();
+fn manifest_path(command: &ArgMatches) -> PathBuf {
+	PathBuf::from(command.value_of("manifest").unwrap())
+}
 
-	let files: Vec<PathBuf> = files
-		.iter()
-		.map(|item| {
-			let file_path = dir.clone().join(item);
+fn run_build(log: &Log, command: &ArgMatches) -> Result<(), String> {
+	let files: Vec<PathBuf> = command.values_of("list").unwrap().map(PathBuf::from).collect();
+	let texture_format = match command.value_of("texture_format").unwrap() {
+		"none" => None,
+		name => Some(CompressedFormat::from_name(name)?),
+	};
+	let mipmaps = command.is_present("mipmaps");
 
-			if !file_path.is_file() {
-				eprintln!("is not a file {}", file_path.to_string_lossy());
-				panic!();
-			}
-			file_path
-		})
-		.collect();
+	let options = BuildOptions {
+		out_dir: PathBuf::from(command.value_of("output_dir").unwrap()),
+		single_file: command.is_present("single_file"),
+		compression: Compression::from_name(command.value_of("compression").unwrap())?,
+		textures: match mipmaps || texture_format.is_some() {
+			true => Some(TextureBake {
+				mipmaps,
+				format: texture_format,
+			}),
+			false => None,
+		},
+		meshes: match command.is_present("optimize_meshes") {
+			true => Some(MeshBake::default()),
+			false => None,
+		},
+		dry_run: command.is_present("dry_run"),
+		force: command.is_present("force"),
+	};
+
+	build(log, &files, &options)
+}
+
+fn build(log: &Log, files: &Vec<PathBuf>, options: &BuildOptions) -> Result<(), String> {
+	let dir = std::env::current_dir().or(Err("cant read current dir"))?;
 
 	let mut packages = HashMap::new();
-	files.iter().for_each(|path_buf| {
-		let mut package_dir = path_buf.clone();
+	for item in files {
+		let file_path = dir.clone().join(item);
+		if !file_path.is_file() {
+			return Err(format!("is not a file {}", file_path.to_string_lossy()));
+		}
+
+		let mut package_dir = file_path.clone();
 		package_dir.pop();
 
-		let context = std::fs::read_to_string(path_buf).unwrap();
-		println!("{}", context);
+		let context = std::fs::read_to_string(&file_path)
+			.or(Err(format!("cant read file: {}", file_path.to_string_lossy())))?;
+		log.debug(&context);
 
-		let mut data: PackageList = serde_json::from_str(&context).unwrap();
-		println!("{:?}", data);
+		let mut data = ResourceManager::parse_package_list(&context)
+			.or_else(|e| Err(format!("{}: {}", file_path.to_string_lossy(), e)))?;
 
-		data.packages.drain(..).for_each(|mut package| {
+		for mut package in data.packages.drain(..) {
 			if packages.get(&package.name).is_some() {
-				println!(
-					"{}",
-					format!("package: '{}' override", package.name).bright_yellow()
-				);
+				log.warn(&format!("package: '{}' override", package.name));
+			}
+
+			if package.compression.is_none() {
+				package.compression = Some(options.compression);
+			}
+
+			if package.textures.is_none() {
+				package.textures = options.textures;
+			}
+
+			if package.meshes.is_none() {
+				package.meshes = options.meshes;
 			}
 
-			package.resources.iter_mut().for_each(|item| {
+			for item in package.resources.iter_mut() {
 				let item_path = package_dir.clone().join(&item.path);
 				if !item_path.is_file() {
-					eprintln!("is not a file {}", item_path.to_string_lossy());
-					panic!();
+					return Err(format!("is not a file {}", item_path.to_string_lossy()));
 				}
 				item.path = item_path;
-			});
+			}
 
 			packages.insert(package.name.clone(), package);
-		});
-	});
+		}
+	}
 
 	// sort packages by priority
 	let mut packages: Vec<Package> = packages.drain().map(|item| item.1).collect();
@@ -153,10 +314,7 @@ fn build(files: &Vec<OsString>, out_dir: &OsString, single_file: bool) {
 			package.resources.iter().for_each(|resource| {
 				let exists = resources.get(&resource.name).is_some();
 				if exists {
-					println!(
-						"{}",
-						format!("resource: '{:?}' override", resource.name).bright_yellow()
-					);
+					log.warn(&format!("resource: '{:?}' override", resource.name));
 				}
 				resources.insert(resource.name.clone(), resource.clone());
 			});
@@ -166,25 +324,32 @@ fn build(files: &Vec<OsString>, out_dir: &OsString, single_file: bool) {
 			package.resources = package
 				.resources
 				.iter()
-				.map(|resource| {
-					if resources.get(&resource.name).is_some() {
-						resources.remove(&resource.name)
-					} else {
-						None
-					}
-				})
-				.filter(|item| item.is_some())
-				.map(|item| item.unwrap())
+				.filter_map(|resource| resources.remove(&resource.name))
 				.collect();
 		});
 	}
 
-	println!("single_file: {}", single_file);
-	if single_file {
+	log.debug(&format!("single_file: {}", options.single_file));
+	if options.single_file {
+		// one archive has one set of settings, packages can't keep their own
+		if let Some(package) = packages.iter().find(|package| {
+			package.get_compression() != options.compression
+				|| package.textures != options.textures
+				|| package.meshes != options.meshes
+		}) {
+			return Err(format!(
+				"package '{}': compression, textures and meshes settings differ from the build options, they can't be merged into a single file",
+				package.name
+			));
+		}
+
 		// merge packages if needed
 		packages = vec![Package {
 			name: "res".to_string(),
 			priority: 0,
+			compression: Some(options.compression),
+			textures: options.textures,
+			meshes: options.meshes,
 			resources: packages
 				.drain(..)
 				.map(|item| item.resources)
@@ -200,32 +365,194 @@ fn build(files: &Vec<OsString>, out_dir: &OsString, single_file: bool) {
 	}
 
 	// write packages
-	let package_file_dir = dir.clone().join(&out_dir);
-	packages.iter_mut().for_each(|package| {
-		let package_file_path = package_file_dir
-			.clone()
-			.join(format!("{}.tar", package.name));
-
-		println!("{:?}, {:?}", package_file_dir, package_file_path);
-		std::fs::create_dir_all(&package_file_dir).unwrap();
-		let package_file = File::create(package_file_path).unwrap();
-
-		let mut tar_file = Builder::new(package_file);
-
-		package.resources.iter_mut().for_each(|item| {
-			tar_file
-				.append_file(
-					item.name.clone(),
-					&mut File::open(item.path.clone()).unwrap(),
-				)
-				.unwrap();
+	let package_file_dir = dir.clone().join(&options.out_dir);
+	let res_data_path = package_file_dir.clone().join("res.json");
 
-			item.path = diff_paths(&item.path.clone(), &package_file_dir.clone()).unwrap();
-		});
-	});
+	let previous = std::fs::read_to_string(&res_data_path)
+		.ok()
+		.and_then(|data| ResourceManager::parse_package_list(&data).ok());
 
-	let res_data = serde_json::to_string(&PackageList { packages }).unwrap();
-	let res_data_path = package_file_dir.clone().join("res.json");
-	std::fs::write(&res_data_path, res_data)
-		.expect(&format!("Unable to write file: {:?}", res_data_path));
+	if !options.dry_run {
+		std::fs::create_dir_all(&package_file_dir)
+			.or(Err(format!("cant create dir: {}", package_file_dir.to_string_lossy())))?;
+	}
+
+	let mut built = 0;
+	for package in packages.iter_mut() {
+		let package_file_path = package_file_dir.clone().join(package.file_name());
+
+		let previous_package = previous
+			.as_ref()
+			.and_then(|list| list.packages.iter().find(|item| item.name == package.name));
+
+		if let Some(previous_package) = previous_package {
+			if !options.force && is_up_to_date(package, previous_package, &package_file_path, &package_file_dir) {
+				log.info(&format!("package '{}' is up to date", package.name));
+				package.resources = previous_package.resources.clone();
+				continue;
+			}
+		}
+
+		built += 1;
+		if options.dry_run {
+			log.info(&format!(
+				"package '{}' would be built: {}",
+				package.name,
+				package_file_path.to_string_lossy()
+			));
+			continue;
+		}
+
+		log.info(&format!(
+			"build package '{}': {}",
+			package.name,
+			package_file_path.to_string_lossy()
+		));
+		let data = build_package_data(package)?;
+
+		for item in package.resources.iter_mut() {
+			item.path = relative_path(&item.path, &package_file_dir)?;
+		}
+
+		std::fs::write(&package_file_path, data)
+			.or(Err(format!("Unable to write file: {:?}", package_file_path)))?;
+	}
+
+	if options.dry_run {
+		log.info(&format!("{} of {} packages would be built", built, packages.len()));
+		return Ok(());
+	}
+
+	let res_data = serde_json::to_string(&PackageList { packages }).or(Err("cant serialize package list"))?;
+	std::fs::write(&res_data_path, res_data).or(Err(format!("Unable to write file: {:?}", res_data_path)))?;
+
+	log.info(&format!("{} packages built", built));
+	Ok(())
+}
+
+fn relative_path(path: &Path, base: &Path) -> Result<PathBuf, String> {
+	diff_paths(path, base).ok_or(format!("cant make relative path: {}", path.to_string_lossy()))
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+	std::fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+// archive is newer than every resource and the manifest lists the same resources
+fn is_up_to_date(package: &Package, previous: &Package, archive: &Path, out_dir: &Path) -> bool {
+	if package.get_compression() != previous.get_compression()
+		|| package.textures != previous.textures
+		|| package.meshes != previous.meshes
+		|| package.resources.len() != previous.resources.len()
+	{
+		return false;
+	}
+
+	let archive_time = match modified(archive) {
+		Some(time) => time,
+		None => return false,
+	};
+
+	package
+		.resources
+		.iter()
+		.zip(previous.resources.iter())
+		.all(|(resource, previous)| {
+			resource.name == previous.name
+				&& resource.data_type == previous.data_type
+				&& previous.hash.is_some()
+				&& relative_path(&resource.path, out_dir).ok().as_ref() == Some(&previous.path)
+				&& modified(&resource.path).map_or(false, |time| time <= archive_time)
+		})
+}
+
+fn load_manifest(path: &PathBuf) -> Result<ResourceManager, String> {
+	let mut manager = ResourceManager::new();
+	manager.set_mode(LoadMode::Archive);
+	manager.load_package_list(path)?;
+	Ok(manager)
+}
+
+fn list(path: &PathBuf) -> Result<(), String> {
+	let manager = load_manifest(path)?;
+
+	for package in manager.get_package_list()?.packages.iter() {
+		println!(
+			"{} {}",
+			package.file_name().bold(),
+			format!("priority: {}, resources: {}", package.priority, package.resources.len()).dimmed()
+		);
+		for resource in package.resources.iter() {
+			let size = resource.size.map_or("?".to_string(), |size| size.to_string());
+			println!("\t{} [{}] {}", resource.name_str(), resource.data_type, size);
+		}
+	}
+
+	Ok(())
+}
+
+fn verify(log: &Log, path: &PathBuf) -> Result<(), String> {
+	let mut manager = load_manifest(path)?;
+	let packages = manager.get_package_list()?.packages.clone();
+
+	let mut failed = 0;
+	for package in packages.iter() {
+		match manager.load_package(&package.name) {
+			Ok(()) => log.info(&format!("{}: {}", package.file_name(), "ok".bright_green())),
+			Err(err) => {
+				log.error(&format!("{}: {}", package.file_name(), err));
+				failed += 1;
+			}
+		}
+	}
+
+	if failed != 0 {
+		return Err(format!("{} of {} packages are broken", failed, packages.len()));
+	}
+	Ok(())
+}
+
+fn unpack(log: &Log, path: &PathBuf, out_dir: &PathBuf, names: Option<Vec<String>>, dry_run: bool) -> Result<(), String> {
+	let mut manager = load_manifest(path)?;
+	let packages = manager.get_package_list()?.packages.clone();
+
+	if let Some(names) = &names {
+		if let Some(name) = names.iter().find(|name| packages.iter().all(|package| package.name != **name)) {
+			return Err(format!("package '{}' not exists", name));
+		}
+	}
+
+	for package in packages.iter() {
+		if names.as_ref().map_or(false, |names| !names.contains(&package.name)) {
+			continue;
+		}
+
+		manager.load_package(&package.name)?;
+
+		for resource in package.resources.iter() {
+			// names come from the manifest, they must not leave `out_dir`
+			if resource.name.components().any(|e| match e {
+				Component::Normal(_) => false,
+				_ => true,
+			}) {
+				return Err(format!("resource name '{}' is not a relative path", resource.name_str()));
+			}
+
+			let file_path = out_dir.join(&resource.name);
+			log.info(&format!("{} -> {}", resource.name_str(), file_path.to_string_lossy()));
+			if dry_run {
+				continue;
+			}
+
+			let data = manager
+				.get_package_resource_data(&package.name, &resource.name_str())
+				.ok_or(format!("package '{}' has no data for '{}'", package.name, resource.name_str()))?;
+			if let Some(parent) = file_path.parent() {
+				std::fs::create_dir_all(parent).or(Err(format!("cant create dir: {}", parent.to_string_lossy())))?;
+			}
+			std::fs::write(&file_path, data).or(Err(format!("Unable to write file: {:?}", file_path)))?;
+		}
+	}
+
+	Ok(())
 }
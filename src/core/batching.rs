@@ -0,0 +1,86 @@
+extern crate specs;
+use self::specs::prelude::*;
+
+use super::{
+	BufferGeometry, DrawMode, EntityRelations, Lod, SharedGeometry, SharedMaterials, Transform, TransformLock,
+};
+use math::Matrix4;
+
+// Merges geometries of entities under `root` that share the same materials into one entity per material set,
+// transforms have to be updated before. Merged entities keep their transform and children but lose geometry
+// and materials, entities with a `Lod`, a locked transform or a non triangle draw mode are skipped.
+// Returns the new entities.
+pub fn batch_static(world: &mut World, root: Entity) -> Result<Vec<Entity>, String> {
+	let entities = world.get_all_children_entities(root);
+
+	let mut batches: Vec<(SharedMaterials, Vec<(Entity, SharedGeometry, Matrix4<f32>)>)> = Vec::new();
+	{
+		let transform_coll = world.read_storage::<Transform>();
+		let geometry_coll = world.read_storage::<SharedGeometry>();
+		let material_coll = world.read_storage::<SharedMaterials>();
+		let lod_coll = world.read_storage::<Lod>();
+
+		let mut matrix_root = Matrix4::new();
+		if let Some(transform) = transform_coll.get(root) {
+			matrix_root.get_inverse(&(transform.matrix_world * transform.matrix_local));
+		}
+
+		for entity in entities.iter().filter(|e| **e != root) {
+			let (transform, geometry, materials) =
+				match (transform_coll.get(*entity), geometry_coll.get(*entity), material_coll.get(*entity)) {
+					(Some(transform), Some(geometry), Some(materials)) => (transform, geometry, materials),
+					_ => continue,
+				};
+			match transform.lock {
+				TransformLock::None => {}
+				_ => continue,
+			}
+			if lod_coll.get(*entity).is_some() {
+				continue;
+			}
+			if geometry.clone().lock().unwrap().draw_mode != DrawMode::Triangles {
+				continue;
+			}
+
+			let matrix = matrix_root * transform.matrix_world * transform.matrix_local;
+			let item = (*entity, geometry.clone(), matrix);
+			match batches.iter_mut().find(|(batch_materials, _)| batch_materials.ptr_eq(materials)) {
+				Some((_, items)) => items.push(item),
+				None => batches.push((materials.clone(), vec![item])),
+			}
+		}
+	}
+
+	// every batch is merged before the world changes, so a failed merge leaves it untouched
+	let mut merged = Vec::new();
+	for (materials, mut items) in batches.drain(..).filter(|(_, items)| items.len() > 1) {
+		let mut geometry = BufferGeometry::new();
+		for (_, source, matrix) in items.iter_mut() {
+			geometry.append_geometry(&source.lock().unwrap(), matrix)?;
+		}
+		merged.push((materials, items, geometry));
+	}
+
+	let mut created = Vec::new();
+	for (materials, items, geometry) in merged {
+		{
+			let mut geometry_coll = world.write_storage::<SharedGeometry>();
+			let mut material_coll = world.write_storage::<SharedMaterials>();
+			for (entity, _, _) in items.iter() {
+				geometry_coll.remove(*entity);
+				material_coll.remove(*entity);
+			}
+		}
+
+		let entity = world
+			.create_entity()
+			.with(Transform::default())
+			.with(SharedGeometry::new(geometry))
+			.with(materials)
+			.build();
+		world.add_child(root, entity);
+		created.push(entity);
+	}
+
+	Ok(created)
+}
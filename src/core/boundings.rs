@@ -65,6 +65,26 @@ where
 		self.min.is_zero() && self.max.is_zero()
 	}
 
+	pub fn get_min(&self) -> &Vector3<T> {
+		&self.min
+	}
+
+	pub fn get_max(&self) -> &Vector3<T> {
+		&self.max
+	}
+
+	pub fn get_center(&self) -> Vector3<T> {
+		let mut center = Vector3::new_zero();
+		center.add_vectors(&self.min, &self.max).multiply_scalar(T::from_f32(0.5));
+		center
+	}
+
+	pub fn get_size(&self) -> Vector3<T> {
+		let mut size = Vector3::new_zero();
+		size.sub_vectors(&self.max, &self.min);
+		size
+	}
+
 	pub fn diff(&self, other: &Self) -> Vector3<T>  {
 		let mut d1 = self.max.clone();
 		let mut d2 = other.max.clone();
@@ -77,6 +97,7 @@ where
 
 
 
+#[derive(Clone, Debug)]
 pub struct BSphare<T>
 where
 	T:Nums
@@ -92,4 +113,12 @@ where
 	pub fn new(center: Vector3<T>, radius: T) -> Self {
 		Self{center, radius}
 	}
+
+	pub fn get_center(&self) -> &Vector3<T> {
+		&self.center
+	}
+
+	pub fn get_radius(&self) -> T {
+		self.radius
+	}
 }
\ No newline at end of file
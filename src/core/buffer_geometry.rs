@@ -1,6 +1,7 @@
 extern crate uuid;
 extern crate heck;
 extern crate specs;
+extern crate bevy_mikktspace;
 
 
 use self::uuid::Uuid;
@@ -10,7 +11,9 @@ use std::fmt;
 use std::sync::{Arc,Mutex, LockResult, MutexGuard};
 use std::mem;
 use std::error::Error;
+use std::collections::HashMap;
 use self::specs::{Component, VecStorage};
+use self::bevy_mikktspace::Geometry;
 
 
 use math::{
@@ -25,7 +28,10 @@ use math::{
 
 use core::{
 	BBox3,
+	BSphare,
+	MeshBake,
 };
+use super::mesh_optimizer;
 
 #[allow(dead_code)]
 #[derive(Clone, Debug)]
@@ -101,6 +107,85 @@ impl BufferData {
 		self.item_size() * bytes
 	}
 
+	// new buffer with items in `order`
+	pub fn gather(&self, order: &[usize]) -> BufferData {
+		match self {
+			BufferData::Matrix2(a) => BufferData::Matrix2(order.iter().map(|i| a[*i].clone()).collect()),
+			BufferData::Matrix3(a) => BufferData::Matrix3(order.iter().map(|i| a[*i].clone()).collect()),
+			BufferData::Matrix4(a) => BufferData::Matrix4(order.iter().map(|i| a[*i].clone()).collect()),
+			BufferData::Vector2(a) => BufferData::Vector2(order.iter().map(|i| a[*i].clone()).collect()),
+			BufferData::Vector3(a) => BufferData::Vector3(order.iter().map(|i| a[*i].clone()).collect()),
+			BufferData::Vector4(a) => BufferData::Vector4(order.iter().map(|i| a[*i].clone()).collect()),
+			BufferData::F32(a) => BufferData::F32(order.iter().map(|i| a[*i]).collect()),
+			BufferData::I32(a) => BufferData::I32(order.iter().map(|i| a[*i]).collect()),
+			BufferData::U32(a) => BufferData::U32(order.iter().map(|i| a[*i]).collect()),
+			BufferData::I16(a) => BufferData::I16(order.iter().map(|i| a[*i]).collect()),
+			BufferData::U16(a) => BufferData::U16(order.iter().map(|i| a[*i]).collect()),
+			BufferData::I8(a) => BufferData::I8(order.iter().map(|i| a[*i]).collect()),
+			BufferData::U8(a) => BufferData::U8(order.iter().map(|i| a[*i]).collect()),
+		}
+	}
+
+	// components of item `i` rounded to `tolerance`, integers are exact
+	pub fn quantize(&self, i: usize, tolerance: f32, key: &mut Vec<i64>) {
+		let q = |v: f32| if tolerance > 0.0 { (v / tolerance).round() as i64 } else { v.to_bits() as i64 };
+		match self {
+			BufferData::Matrix2(a) => key.extend(a[i].elements.iter().map(|v| q(*v))),
+			BufferData::Matrix3(a) => key.extend(a[i].elements.iter().map(|v| q(*v))),
+			BufferData::Matrix4(a) => key.extend(a[i].elements.iter().map(|v| q(*v))),
+			BufferData::Vector2(a) => key.extend_from_slice(&[q(a[i].x), q(a[i].y)]),
+			BufferData::Vector3(a) => key.extend_from_slice(&[q(a[i].x), q(a[i].y), q(a[i].z)]),
+			BufferData::Vector4(a) => key.extend_from_slice(&[q(a[i].x), q(a[i].y), q(a[i].z), q(a[i].w)]),
+			BufferData::F32(a) => key.push(q(a[i])),
+			BufferData::I32(a) => key.push(a[i] as i64),
+			BufferData::U32(a) => key.push(a[i] as i64),
+			BufferData::I16(a) => key.push(a[i] as i64),
+			BufferData::U16(a) => key.push(a[i] as i64),
+			BufferData::I8(a) => key.push(a[i] as i64),
+			BufferData::U8(a) => key.push(a[i] as i64),
+		}
+	}
+
+	// appends a copy of item `i`
+	pub fn push_copy(&mut self, i: usize) {
+		match self {
+			BufferData::Matrix2(a) => { let e = a[i].clone(); a.push(e) }
+			BufferData::Matrix3(a) => { let e = a[i].clone(); a.push(e) }
+			BufferData::Matrix4(a) => { let e = a[i].clone(); a.push(e) }
+			BufferData::Vector2(a) => { let e = a[i].clone(); a.push(e) }
+			BufferData::Vector3(a) => { let e = a[i].clone(); a.push(e) }
+			BufferData::Vector4(a) => { let e = a[i].clone(); a.push(e) }
+			BufferData::F32(a) => { let e = a[i]; a.push(e) }
+			BufferData::I32(a) => { let e = a[i]; a.push(e) }
+			BufferData::U32(a) => { let e = a[i]; a.push(e) }
+			BufferData::I16(a) => { let e = a[i]; a.push(e) }
+			BufferData::U16(a) => { let e = a[i]; a.push(e) }
+			BufferData::I8(a) => { let e = a[i]; a.push(e) }
+			BufferData::U8(a) => { let e = a[i]; a.push(e) }
+		}
+	}
+
+	// appends items of `other`, false if the types differ
+	pub fn append(&mut self, other: &BufferData) -> bool {
+		match (self, other) {
+			(BufferData::Matrix2(a), BufferData::Matrix2(b)) => a.extend_from_slice(b),
+			(BufferData::Matrix3(a), BufferData::Matrix3(b)) => a.extend_from_slice(b),
+			(BufferData::Matrix4(a), BufferData::Matrix4(b)) => a.extend_from_slice(b),
+			(BufferData::Vector2(a), BufferData::Vector2(b)) => a.extend_from_slice(b),
+			(BufferData::Vector3(a), BufferData::Vector3(b)) => a.extend_from_slice(b),
+			(BufferData::Vector4(a), BufferData::Vector4(b)) => a.extend_from_slice(b),
+			(BufferData::F32(a), BufferData::F32(b)) => a.extend_from_slice(b),
+			(BufferData::I32(a), BufferData::I32(b)) => a.extend_from_slice(b),
+			(BufferData::U32(a), BufferData::U32(b)) => a.extend_from_slice(b),
+			(BufferData::I16(a), BufferData::I16(b)) => a.extend_from_slice(b),
+			(BufferData::U16(a), BufferData::U16(b)) => a.extend_from_slice(b),
+			(BufferData::I8(a), BufferData::I8(b)) => a.extend_from_slice(b),
+			(BufferData::U8(a), BufferData::U8(b)) => a.extend_from_slice(b),
+			_ => return false,
+		}
+		true
+	}
+
 	pub fn definition(&self) -> String {
 		match self {
 			BufferData::Matrix2(_) => "MAT2".to_string(),
@@ -121,6 +206,34 @@ impl BufferData {
 }
 
 
+// primitive assembly of indices, only `Triangles` can be simplified, optimized and batched
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DrawMode {
+	Points,
+	Lines,
+	LineStrip,
+	LineLoop,
+	Triangles,
+	TriangleStrip,
+	TriangleFan,
+}
+
+impl DrawMode {
+	// index lists that stay valid when concatenated
+	pub fn is_list(&self) -> bool {
+		match self {
+			DrawMode::Points | DrawMode::Lines | DrawMode::Triangles => true,
+			_ => false,
+		}
+	}
+}
+
+impl Default for DrawMode {
+	fn default() -> Self {
+		DrawMode::Triangles
+	}
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum BufferType {
 	Position,
@@ -206,7 +319,10 @@ pub struct BufferGeometry {
 	pub indices: Vec<u32>,
 	pub attributes: Vec<BufferAttribute>,
 	pub buffer_order: Vec<BufferType>,
+	pub draw_mode: DrawMode,
 	pub b_box: Option<BBox3<f32>>,
+	pub b_sphere: Option<BSphare<f32>>,
+	bounds_need_update: bool,
 	callbacks: Vec<fn(&mut BufferGeometry)>,
 }
 
@@ -217,7 +333,9 @@ impl fmt::Debug for BufferGeometry {
 BufferGeometry: {}
 uuid: {}
 groups: {:?}
+draw_mode: {:?}
 b_box: {:?}
+b_sphere: {:?}
 callbacks: {}
 indices: {:?}
 attributes: {:?}
@@ -225,7 +343,9 @@ attributes: {:?}
 		self.name,
 		self.uuid,
 		self.groups,
+		self.draw_mode,
 		self.b_box,
+		self.b_sphere,
 		self.callbacks.len(),
 		self.indices,
 		self.attributes,
@@ -244,8 +364,11 @@ impl BufferGeometry {
 			uuid: Uuid::new_v4(),
 			callbacks: Vec::new(),
 			name: "".to_string(),
+			draw_mode: DrawMode::Triangles,
 			b_box: None,
-			buffer_order: vec![BufferType::Position, BufferType::Normal, BufferType::UV(0), BufferType::Color(0), BufferType::Joint(0), BufferType::Weight(0)],
+			b_sphere: None,
+			bounds_need_update: true,
+			buffer_order: vec![BufferType::Position, BufferType::Normal, BufferType::UV(0), BufferType::Color(0), BufferType::Joint(0), BufferType::Weight(0), BufferType::Tangent],
 		}
 	}
 
@@ -296,6 +419,9 @@ impl BufferGeometry {
 		if let Some(index) = index {
 			self.attributes.remove(index);
 		}
+		if buffer_attribute.buffer_type == BufferType::Position {
+			self.bounds_need_update = true;
+		}
 		self.attributes.push(buffer_attribute);
 
 		if !self.attributes.iter().all( |e| e.len() == self.attributes[0].len() ) {
@@ -334,7 +460,11 @@ impl BufferGeometry {
 		self.attributes.iter().any(|e| e.buffer_type == buffer_type)
 	}
 
+	// positions may change through the returned attribute, so bounds get recomputed
 	pub fn get_attribute_mut(&mut self, buffer_type: BufferType) -> Option<&mut BufferAttribute> {
+		if buffer_type == BufferType::Position {
+			self.bounds_need_update = true;
+		}
 		self.attributes.iter_mut().find(|e| e.buffer_type == buffer_type)
 	}
 
@@ -386,35 +516,492 @@ impl BufferGeometry {
 		}
 	}
 
+	// MikkTSpace tangents from positions, normals and UV(0), `w` is the bitangent sign,
+	// vertices shared by triangles with mirrored uv are split
+	pub fn generate_tangents(&mut self) -> Result<(), &str> {
+		if self.indices.len() == 0 && self.gen_indices().is_err() {
+			return Err("BufferGeometry: cant find position");
+		}
+
+		let corners = {
+			let positions = self.get_attribute(BufferType::Position).map(|e| &e.data);
+			let normals = self.get_attribute(BufferType::Normal).map(|e| &e.data);
+			let uvs = self.get_attribute(BufferType::UV(0)).map(|e| &e.data);
+
+			match (positions, normals, uvs) {
+				(Some(BufferData::Vector3(p)), Some(BufferData::Vector3(n)), Some(BufferData::Vector2(uv))) => {
+					let mut geometry = MikkTSpace {
+						indices: &self.indices,
+						positions: p,
+						normals: n,
+						uvs: uv,
+						tangents: vec![Vector4::new(1.0, 0.0, 0.0, 1.0); self.indices.len()],
+					};
+					if !bevy_mikktspace::generate_tangents(&mut geometry) {
+						return Err("BufferGeometry: cant generate tangents");
+					}
+					geometry.tangents
+				}
+				_ => return Err("BufferGeometry: tangents need position, normal and uv"),
+			}
+		};
+
+		let mut tangents: Vec<Option<Vector4<f32>>> = vec![None; self.get_attribute(BufferType::Position).unwrap().len()];
+		let mut splits = HashMap::new();
+
+		for (i, tangent) in corners.into_iter().enumerate() {
+			let vertex = self.indices[i] as usize;
+			let w = match &tangents[vertex] {
+				None => {
+					tangents[vertex] = Some(tangent);
+					continue;
+				}
+				Some(current) => current.w,
+			};
+			if w == tangent.w {
+				continue;
+			}
+
+			let index = match splits.get(&vertex) {
+				Some(index) => *index,
+				None => {
+					self.attributes.iter_mut().for_each(|attr| attr.data.push_copy(vertex));
+					tangents.push(Some(tangent));
+					splits.insert(vertex, tangents.len() - 1);
+					tangents.len() - 1
+				}
+			};
+			self.indices[i] = index as u32;
+		}
+
+		let tangents = tangents
+			.into_iter()
+			.map(|e| e.unwrap_or(Vector4::new(1.0, 0.0, 0.0, 1.0)))
+			.collect();
+		self.create_buffer_attribute(BufferType::Tangent, BufferData::Vector4(tangents));
+		Ok(())
+	}
+
+	// merges vertices whose attributes are equal within `tolerance`, rebuilds indices
+	pub fn merge_vertices(&mut self, tolerance: f32) {
+		let count = self.vertex_count();
+		if self.indices.len() == 0 {
+			self.indices = (0..count as u32).collect();
+		}
+
+		let mut merged = HashMap::new();
+		let mut order = Vec::new();
+		let mut key = Vec::new();
+		let remap: Vec<u32> = (0..count)
+			.map(|i| {
+				key.clear();
+				self.attributes.iter().for_each(|attr| attr.data.quantize(i, tolerance, &mut key));
+				*merged.entry(key.clone()).or_insert_with(|| {
+					order.push(i);
+					order.len() as u32 - 1
+				})
+			})
+			.collect();
+
+		self.indices.iter_mut().for_each(|i| *i = remap[*i as usize]);
+		self.gather_vertices(&order);
+	}
+
+	// one vertex per triangle corner, indices become sequential
+	pub fn to_non_indexed(&mut self) {
+		if self.indices.len() == 0 {
+			return;
+		}
+		let order: Vec<usize> = self.indices.iter().map(|i| *i as usize).collect();
+		self.gather_vertices(&order);
+		self.indices = (0..order.len() as u32).collect();
+	}
+
+	pub fn remove_unused_vertices(&mut self) {
+		if self.indices.len() == 0 {
+			return;
+		}
+
+		let mut remap = vec![None; self.vertex_count()];
+		let mut order = Vec::new();
+		for i in self.indices.iter_mut() {
+			let index = *remap[*i as usize].get_or_insert_with(|| {
+				order.push(*i as usize);
+				order.len() as u32 - 1
+			});
+			*i = index;
+		}
+
+		self.gather_vertices(&order);
+	}
+
+	// triangle order for the post transform cache, per group
+	pub fn optimize_vertex_cache(&mut self) {
+		for (start, end) in self.group_ranges() {
+			mesh_optimizer::optimize_vertex_cache(&mut self.indices[start..end]);
+		}
+	}
+
+	// run after `optimize_vertex_cache`, `threshold` limits the cache efficiency
+	// traded for less overdraw, 1.05 is a good default
+	pub fn optimize_overdraw(&mut self, threshold: f32) {
+		let ranges = self.group_ranges();
+		let positions = match self.attributes.iter().find(|e| e.buffer_type == BufferType::Position).map(|e| &e.data) {
+			Some(BufferData::Vector3(positions)) => positions,
+			_ => return,
+		};
+		for (start, end) in ranges {
+			mesh_optimizer::optimize_overdraw(&mut self.indices[start..end], positions, threshold);
+		}
+	}
+
+	// vertices in order of first use, unused ones are removed
+	pub fn optimize_vertex_fetch(&mut self) {
+		self.remove_unused_vertices();
+	}
+
+	pub fn optimize(&mut self, bake: &MeshBake) {
+		if self.indices.len() == 0 {
+			return;
+		}
+		let triangles = self.draw_mode == DrawMode::Triangles;
+		if bake.vertex_cache && triangles {
+			self.optimize_vertex_cache();
+		}
+		if let (Some(threshold), true) = (bake.overdraw, triangles) {
+			self.optimize_overdraw(threshold);
+		}
+		if bake.vertex_fetch {
+			self.optimize_vertex_fetch();
+		}
+	}
+
+	// reduces triangles towards `target_triangles` while the error stays under `max_error`
+	// (relative to the geometry extent), returns the reached error;
+	// seams, group boundaries and open borders are kept, indexed geometry needs `merge_vertices` first
+	pub fn simplify(&mut self, target_triangles: usize, max_error: f32) -> f32 {
+		if self.indices.len() == 0 || self.draw_mode != DrawMode::Triangles {
+			return 0.0;
+		}
+		let ranges = self.group_ranges();
+		let total = ranges.iter().map(|(start, end)| end - start).sum::<usize>().max(1);
+
+		let mut error = 0.0f32;
+		let mut indices = Vec::with_capacity(self.indices.len());
+		{
+			let positions = match self.attributes.iter().find(|e| e.buffer_type == BufferType::Position).map(|e| &e.data) {
+				Some(BufferData::Vector3(positions)) => positions,
+				_ => return 0.0,
+			};
+
+			// vertices shared by several groups
+			let mut owner = vec![None; positions.len()];
+			let mut locked = vec![false; positions.len()];
+			for (group, (start, end)) in ranges.iter().enumerate() {
+				for i in self.indices[*start..*end].iter() {
+					match owner[*i as usize] {
+						None => owner[*i as usize] = Some(group),
+						Some(other) if other != group => locked[*i as usize] = true,
+						_ => {}
+					}
+				}
+			}
+
+			for (group, (start, end)) in ranges.iter().enumerate() {
+				let target = target_triangles * 3 * (end - start) / total / 3 * 3;
+				let (group_indices, group_error) =
+					mesh_optimizer::simplify(&self.indices[*start..*end], positions, &locked, target, max_error);
+				if let Some(group) = self.groups.get_mut(group) {
+					group.start = indices.len();
+					group.count = group_indices.len();
+				}
+				indices.extend(group_indices);
+				error = error.max(group_error);
+			}
+		}
+
+		self.indices = indices;
+		self.remove_unused_vertices();
+		error
+	}
+
+	// one geometry from `sources` with their matrices applied, see `append_geometry`
+	pub fn merge(sources: &[(&BufferGeometry, Matrix4<f32>)]) -> Result<BufferGeometry, String> {
+		let mut merged = BufferGeometry::new();
+		for (geometry, matrix) in sources {
+			merged.append_geometry(geometry, matrix)?;
+		}
+		Ok(merged)
+	}
+
+	// appends `other` with `matrix` applied, its groups are kept (one group if it has none),
+	// attributes have to match unless this geometry is empty
+	pub fn append_geometry(&mut self, other: &BufferGeometry, matrix: &Matrix4<f32>) -> Result<(), String> {
+		// everything is checked before appending, so a failed merge leaves `self` untouched
+		let empty = self.attributes.len() == 0;
+		let draw_mode = if self.vertex_count() == 0 { other.draw_mode } else { self.draw_mode };
+		if !other.draw_mode.is_list() || other.draw_mode != draw_mode {
+			return Err(format!("BufferGeometry: can't merge \"{}\", draw mode {:?}", other.name, other.draw_mode));
+		}
+
+		if !empty {
+			if other.attributes.len() != self.attributes.len() {
+				return Err(format!("BufferGeometry: can't merge \"{}\", attributes differ", other.name));
+			}
+			let differs = self.attributes.iter().find(|attr| {
+				other
+					.get_attribute(attr.buffer_type.clone())
+					.map_or(true, |source| source.definition() != attr.definition())
+			});
+			if let Some(attr) = differs {
+				return Err(format!(
+					"BufferGeometry: can't merge \"{}\", attribute {:?} differs",
+					other.name, attr.buffer_type
+				));
+			}
+		}
+
+		if empty {
+			self.buffer_order = other.buffer_order.clone();
+			self.attributes = other
+				.attributes
+				.iter()
+				.map(|attr| BufferAttribute { data: attr.data.gather(&[]), ..attr.clone() })
+				.collect();
+		}
+		self.draw_mode = draw_mode;
+
+		let mut geometry = BufferGeometry::new();
+		geometry.draw_mode = other.draw_mode;
+		geometry.attributes = other.attributes.clone();
+		geometry.indices = if other.indices.len() == 0 {
+			(0..other.vertex_count() as u32).collect()
+		} else {
+			other.indices.clone()
+		};
+		geometry.apply_matrix4(matrix);
+
+		let offset = self.vertex_count() as u32;
+		let start = self.indices.len();
+		for attr in self.attributes.iter_mut() {
+			if let Some(source) = geometry.get_attribute(attr.buffer_type.clone()) {
+				attr.data.append(&source.data);
+			}
+		}
+
+		if other.groups.len() == 0 {
+			self.groups.push(BufferGroup {
+				start,
+				count: geometry.indices.len(),
+				material_index: 0,
+				name: Some(other.name.clone()),
+			});
+		} else {
+			self.groups.extend(other.groups.iter().map(|group| BufferGroup {
+				start: start + group.start,
+				..group.clone()
+			}));
+		}
+		self.indices.extend(geometry.indices.iter().map(|i| i + offset));
+		self.bounds_need_update = true;
+
+		Ok(())
+	}
+
+	// transforms positions, normals and tangents in place, mirroring matrices flip the winding
+	pub fn apply_matrix4(&mut self, matrix: &Matrix4<f32>) -> &mut Self {
+		let mut matrix_normal = Matrix3::new();
+		matrix_normal.get_normal_matrix(matrix);
+		let mut matrix_tangent = Matrix3::new();
+		matrix_tangent.set_from_matrix4(matrix);
+		let mirrored = matrix.determinant() < 0.0;
+
+		for attr in self.attributes.iter_mut() {
+			match (&attr.buffer_type, &mut attr.data) {
+				(BufferType::Position, BufferData::Vector3(positions)) => {
+					positions.iter_mut().for_each(|e| {
+						e.apply_matrix_4(matrix);
+					});
+				}
+				(BufferType::Normal, BufferData::Vector3(normals)) => {
+					normals.iter_mut().for_each(|e| {
+						e.apply_matrix_3(&matrix_normal).normalize();
+					});
+				}
+				(BufferType::Tangent, BufferData::Vector4(tangents)) => {
+					tangents.iter_mut().for_each(|e| {
+						let mut t = Vector3::new(e.x, e.y, e.z);
+						t.apply_matrix_3(&matrix_tangent).normalize();
+						let w = if mirrored { -e.w } else { e.w };
+						*e = Vector4::new(t.x, t.y, t.z, w);
+					});
+				}
+				_ => {}
+			}
+		}
+
+		// mirroring flips the winding, not indexed triangles swap their vertices instead
+		if mirrored && self.draw_mode == DrawMode::Triangles {
+			if self.indices.len() == 0 {
+				let count = self.vertex_count();
+				let order: Vec<usize> = (0..count)
+					.map(|i| match i % 3 {
+						1 if i + 1 < count => i + 1,
+						2 => i - 1,
+						_ => i,
+					})
+					.collect();
+				self.gather_vertices(&order);
+			} else {
+				self.indices.chunks_mut(3).filter(|tri| tri.len() == 3).for_each(|tri| tri.swap(1, 2));
+			}
+		}
+		self.bounds_need_update = true;
+		self
+	}
+
+	pub fn translate(&mut self, x: f32, y: f32, z: f32) -> &mut Self {
+		let mut matrix = Matrix4::new();
+		matrix.make_translation(x, y, z);
+		self.apply_matrix4(&matrix)
+	}
+
+	pub fn rotate_x(&mut self, angle: f32) -> &mut Self {
+		let mut matrix = Matrix4::new();
+		matrix.make_rotation_x(angle);
+		self.apply_matrix4(&matrix)
+	}
+
+	pub fn rotate_y(&mut self, angle: f32) -> &mut Self {
+		let mut matrix = Matrix4::new();
+		matrix.make_rotation_y(angle);
+		self.apply_matrix4(&matrix)
+	}
+
+	pub fn rotate_z(&mut self, angle: f32) -> &mut Self {
+		let mut matrix = Matrix4::new();
+		matrix.make_rotation_z(angle);
+		self.apply_matrix4(&matrix)
+	}
+
+	pub fn scale(&mut self, x: f32, y: f32, z: f32) -> &mut Self {
+		let mut matrix = Matrix4::new();
+		matrix.make_scale(x, y, z);
+		self.apply_matrix4(&matrix)
+	}
+
+	// moves the bounding box center to the origin
+	pub fn center(&mut self) -> &mut Self {
+		if let Some(b_box) = self.compute_box3() {
+			let center = b_box.get_center();
+			self.translate(-center.x, -center.y, -center.z);
+		}
+		self
+	}
+
+	// centers and scales to fit the unit sphere
+	pub fn normalize(&mut self) -> &mut Self {
+		if let Some(sphere) = self.compute_bounding_sphere() {
+			let center = sphere.get_center().clone();
+			let radius = sphere.get_radius();
+			let scale = if radius == 0.0 { 1.0 } else { 1.0 / radius };
+			self.translate(-center.x, -center.y, -center.z);
+			self.scale(scale, scale, scale);
+		}
+		self
+	}
+
+	// centered on the bounding box, None without positions
+	pub fn compute_bounding_sphere(&self) -> Option<BSphare<f32>> {
+		let center = self.compute_box3()?.get_center();
+		let radius_sq = self.get_positions()?.iter().fold(0.0f32, |radius, position| {
+			let mut offset = position.clone();
+			offset.sub(&center);
+			radius.max(offset.length_sq())
+		});
+		Some(BSphare::new(center, radius_sq.sqrt()))
+	}
+
+	fn compute_box3(&self) -> Option<BBox3<f32>> {
+		let positions = self.get_positions()?;
+		if positions.len() == 0 {
+			return None;
+		}
+		let mut b_box = BBox3::new_empty();
+		b_box.set_from_array(&positions[..]);
+		Some(b_box)
+	}
+
+	fn get_positions(&self) -> Option<&Vec<Vector3<f32>>> {
+		match self.get_attribute(BufferType::Position).map(|attr| &attr.data) {
+			Some(BufferData::Vector3(positions)) => Some(positions),
+			_ => None,
+		}
+	}
+
+	// index ranges of groups, whole geometry without groups
+	fn group_ranges(&self) -> Vec<(usize, usize)> {
+		if self.groups.len() == 0 {
+			return vec![(0, self.indices.len() / 3 * 3)];
+		}
+		self.groups
+			.iter()
+			.map(|group| {
+				let end = (group.start + group.count).min(self.indices.len());
+				let start = group.start.min(end);
+				(start, start + (end - start) / 3 * 3)
+			})
+			.collect()
+	}
+
+	pub fn vertex_count(&self) -> usize {
+		self.attributes.get(0).map_or(0, |attr| attr.len())
+	}
+
+	fn gather_vertices(&mut self, order: &[usize]) {
+		self.attributes.iter_mut().for_each(|attr| attr.data = attr.data.gather(order));
+		self.bounds_need_update = true;
+	}
+
 	pub fn duplicate(&self) -> Self {
 		let mut data = self.clone();
 		data.uuid = Uuid::new_v4();
 		data
 	}
 
-	pub fn update_box3 (&mut self) -> Result <(), Box<Error>> {
-		let mut b_box = None;
-		if let Some(attr) = self.get_attribute(BufferType::Position) {
-			if let BufferData::Vector3(positions) = &attr.data {
-				let mut b = BBox3::new_empty();
-				b.set_from_array(&positions[..]);
-				b_box = Some(b);
-			}
-		}
-		if b_box.is_none() {return Err( Box::from("cant update b_box") ); }
+	// marks cached b_box and b_sphere as stale, needed after editing positions by hand
+	pub fn need_update_bounds(&mut self) {
+		self.bounds_need_update = true;
+	}
+
+	pub fn update_bounds(&mut self) -> Result <(), Box<Error>> {
+		let b_box = self.compute_box3();
+		let b_sphere = self.compute_bounding_sphere();
+		if b_box.is_none() || b_sphere.is_none() {return Err( Box::from("cant update bounds") ); }
 		self.b_box = b_box;
+		self.b_sphere = b_sphere;
+		self.bounds_need_update = false;
 		Ok(())
 	}
 
+	pub fn update_box3 (&mut self) -> Result <(), Box<Error>> {
+		self.update_bounds()
+	}
+
 	pub fn get_b_box(&mut self) -> Result<BBox3<f32>, Box<Error>> {
-		if self.b_box.is_some() {
-			return Ok(self.b_box.as_ref().unwrap().clone())
+		if self.bounds_need_update || self.b_box.is_none() {
+			self.update_bounds()?;
 		}
-
-		self.update_box3()?;
 		Ok(self.b_box.as_ref().unwrap().clone())
 	}
 
+	pub fn get_b_sphere(&mut self) -> Result<BSphare<f32>, Box<Error>> {
+		if self.bounds_need_update || self.b_sphere.is_none() {
+			self.update_bounds()?;
+		}
+		Ok(self.b_sphere.as_ref().unwrap().clone())
+	}
+
 	pub fn scale_positions_by_vec(&mut self, v: &Vector3<f32>) -> Option<()> {
 		if let Some(attr) = self.get_attribute_mut(BufferType::Position) {
 			if let BufferData::Vector3(positions) = &mut attr.data {
@@ -433,6 +1020,49 @@ impl BufferGeometry {
 	pub fn get_vertex_byte_size(&self) -> usize {
 		self.iter_attributes().map(|attr| attr.data.elem_byte_len()).sum()
 	}
+
+	pub fn memory_size(&self) -> usize {
+		let attributes: usize = self.attributes.iter().map(|attr| attr.data.len() * attr.data.elem_byte_len()).sum();
+		attributes + self.indices.len() * mem::size_of::<u32>()
+	}
+}
+
+// triangle list view for `bevy_mikktspace`, tangents are per corner
+struct MikkTSpace<'a> {
+	indices: &'a [u32],
+	positions: &'a [Vector3<f32>],
+	normals: &'a [Vector3<f32>],
+	uvs: &'a [Vector2<f32>],
+	tangents: Vec<Vector4<f32>>,
+}
+
+impl<'a> Geometry for MikkTSpace<'a> {
+	fn num_faces(&self) -> usize {
+		self.indices.len() / 3
+	}
+
+	fn num_vertices_of_face(&self, _face: usize) -> usize {
+		3
+	}
+
+	fn position(&self, face: usize, vert: usize) -> [f32; 3] {
+		let p = &self.positions[self.indices[face * 3 + vert] as usize];
+		[p.x, p.y, p.z]
+	}
+
+	fn normal(&self, face: usize, vert: usize) -> [f32; 3] {
+		let n = &self.normals[self.indices[face * 3 + vert] as usize];
+		[n.x, n.y, n.z]
+	}
+
+	fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+		let uv = &self.uvs[self.indices[face * 3 + vert] as usize];
+		[uv.x, uv.y]
+	}
+
+	fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, vert: usize) {
+		self.tangents[face * 3 + vert] = Vector4::new(tangent[0], tangent[1], tangent[2], tangent[3]);
+	}
 }
 
 impl Drop for BufferGeometry {
@@ -457,6 +1087,14 @@ impl SharedGeometry {
 	pub fn lock(&mut self) -> LockResult<MutexGuard<BufferGeometry>> {
 		self.0.lock()
 	}
+
+	pub fn ref_count(&self) -> usize {
+		Arc::strong_count(&self.0)
+	}
+
+	pub fn ptr_eq(&self, other: &Self) -> bool {
+		Arc::ptr_eq(&self.0, &other.0)
+	}
 }
 
 
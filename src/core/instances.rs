@@ -0,0 +1,141 @@
+extern crate specs;
+extern crate uuid;
+
+use self::specs::{Component, VecStorage};
+use self::uuid::Uuid;
+
+use super::{BufferAttribute, BufferData, BufferType};
+use math::Matrix4;
+
+// first attribute location of instance data, locations below belong to geometry attributes
+pub const INSTANCE_ATTRIBUTE_LOCATION: usize = 8;
+
+// Per instance transforms (applied in model space) and attributes for an entity with `SharedGeometry`,
+// drawn with one instanced call. Materials get a separate program for every set of instance attributes.
+#[derive(Clone, Debug)]
+pub struct Instances {
+	pub uuid: Uuid,
+	transforms: Vec<Matrix4<f32>>,
+	attributes: Vec<BufferAttribute>,
+	version: usize,
+}
+
+#[allow(dead_code)]
+impl Instances {
+	pub fn new() -> Self {
+		Self::from_transforms(Vec::new())
+	}
+
+	pub fn from_transforms(transforms: Vec<Matrix4<f32>>) -> Self {
+		Self {
+			uuid: Uuid::new_v4(),
+			transforms,
+			attributes: Vec::new(),
+			version: 0,
+		}
+	}
+
+	pub fn len(&self) -> usize {
+		self.transforms.len()
+	}
+
+	// attributes can get out of sync with transforms through `get_transforms_mut` or `get_attribute_mut`
+	pub fn is_valid(&self) -> bool {
+		self.attributes.iter().all(|attr| attr.len() == self.transforms.len())
+	}
+
+	pub fn get_version(&self) -> usize {
+		self.version
+	}
+
+	pub fn get_transforms(&self) -> &[Matrix4<f32>] {
+		&self.transforms
+	}
+
+	// attributes have to be updated to the new length
+	pub fn get_transforms_mut(&mut self) -> &mut Vec<Matrix4<f32>> {
+		self.version += 1;
+		&mut self.transforms
+	}
+
+	pub fn set_transform(&mut self, index: usize, matrix: Matrix4<f32>) {
+		self.transforms[index] = matrix;
+		self.version += 1;
+	}
+
+	pub fn create_buffer_attribute(&mut self, buffer_type: BufferType, data: BufferData) -> &mut BufferAttribute {
+		if data.len() != self.transforms.len() {
+			panic!("Instances: {:?} has {} items for {} instances", buffer_type, data.len(), self.transforms.len());
+		}
+
+		self.attributes.retain(|attr| attr.buffer_type != buffer_type);
+		self.attributes.push(BufferAttribute {
+			buffer_type,
+			data,
+			normalized: false,
+			dynamic: true,
+		});
+		self.version += 1;
+
+		let i = self.attributes.len() - 1;
+		&mut self.attributes[i]
+	}
+
+	pub fn get_attribute(&self, buffer_type: BufferType) -> Option<&BufferAttribute> {
+		self.attributes.iter().find(|e| e.buffer_type == buffer_type)
+	}
+
+	pub fn get_attribute_mut(&mut self, buffer_type: BufferType) -> Option<&mut BufferAttribute> {
+		self.version += 1;
+		self.attributes.iter_mut().find(|e| e.buffer_type == buffer_type)
+	}
+
+	pub fn iter_attributes(&self) -> impl Iterator<Item = &BufferAttribute> {
+		self.attributes.iter()
+	}
+
+	// first location of every attribute, the transform takes four from `INSTANCE_ATTRIBUTE_LOCATION`
+	pub fn attribute_locations(&self) -> Vec<usize> {
+		let mut location = INSTANCE_ATTRIBUTE_LOCATION + 4;
+		self.attributes
+			.iter()
+			.map(|attr| {
+				let start = location;
+				location += attribute_columns(&attr.data);
+				start
+			})
+			.collect()
+	}
+
+	// shader definitions with attribute locations as values
+	pub fn definitions(&self) -> Vec<(String, usize)> {
+		let mut definitions = vec![
+			("INSTANCED".to_string(), 1),
+			("INSTANCE_MATRIX".to_string(), INSTANCE_ATTRIBUTE_LOCATION),
+		];
+		definitions.extend(self.attributes.iter().zip(self.attribute_locations()).map(|(attr, location)| {
+			(format!("INSTANCE_{}_{}", attr.buffer_type.definition(), attr.data.definition()), location)
+		}));
+		definitions
+	}
+}
+
+impl Default for Instances {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Component for Instances {
+	type Storage = VecStorage<Self>;
+}
+
+// attribute locations taken by one item
+pub fn attribute_columns(data: &BufferData) -> usize {
+	match data {
+		BufferData::Matrix2(_) => 2,
+		BufferData::Matrix3(_) => 3,
+		BufferData::Matrix4(_) => 4,
+		_ => 1,
+	}
+}
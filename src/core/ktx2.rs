@@ -0,0 +1,204 @@
+extern crate byteorder;
+
+use self::byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use super::{mip_size, CompressedFormat};
+use std::io::Cursor;
+
+const IDENTIFIER: [u8; 12] = [0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A];
+const LEVEL_INDEX_OFFSET: usize = 80;
+
+// KTX2 texture without supercompression, single face and layer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ktx2 {
+	pub vk_format: u32,
+	pub width: u32,
+	pub height: u32,
+	// level 0 first
+	pub levels: Vec<Vec<u8>>,
+}
+
+impl Ktx2 {
+	pub const VK_R8_UNORM: u32 = 9;
+	pub const VK_R8G8_UNORM: u32 = 16;
+	pub const VK_R8G8B8_UNORM: u32 = 23;
+	pub const VK_R8G8B8A8_UNORM: u32 = 37;
+
+	pub fn is_ktx2(data: &[u8]) -> bool {
+		data.len() >= IDENTIFIER.len() && data[..IDENTIFIER.len()] == IDENTIFIER
+	}
+
+	pub fn parse(data: &[u8]) -> Result<Self, String> {
+		if !Self::is_ktx2(data) {
+			return Err("not a ktx2 file".to_string());
+		}
+		if data.len() < LEVEL_INDEX_OFFSET {
+			return Err("ktx2 file is truncated".to_string());
+		}
+
+		let mut header = Cursor::new(&data[IDENTIFIER.len()..]);
+		let mut fields = [0u32; 9];
+		for field in fields.iter_mut() {
+			*field = header.read_u32::<LittleEndian>().unwrap();
+		}
+		let [vk_format, _type_size, width, height, depth, layers, faces, level_count, supercompression] = fields;
+
+		if supercompression != 0 {
+			return Err(format!("ktx2 supercompression {} is not supported", supercompression));
+		}
+		if depth > 1 || layers > 1 || faces != 1 {
+			return Err("only 2d ktx2 textures are supported".to_string());
+		}
+		if width == 0 || height == 0 {
+			return Err("ktx2 texture is empty".to_string());
+		}
+		let max_levels = 32 - width.max(height).leading_zeros();
+		if level_count > max_levels {
+			return Err(format!("ktx2 texture has {} levels, {}x{} allows {}", level_count, width, height, max_levels));
+		}
+
+		let mut index = Cursor::new(&data[LEVEL_INDEX_OFFSET..]);
+		let mut levels = Vec::new();
+		for i in 0..level_count.max(1) as usize {
+			let offset = index.read_u64::<LittleEndian>().or(Err("ktx2 file is truncated"))? as usize;
+			let length = index.read_u64::<LittleEndian>().or(Err("ktx2 file is truncated"))? as usize;
+			index.read_u64::<LittleEndian>().or(Err("ktx2 file is truncated"))?;
+
+			let level = offset
+				.checked_add(length)
+				.and_then(|end| data.get(offset..end))
+				.ok_or("ktx2 file is truncated")?;
+			// uploads read the full level size from the data
+			let expected = level_size(vk_format, mip_size(width, i), mip_size(height, i))?;
+			if level.len() != expected {
+				return Err(format!("ktx2 level {} has {} bytes instead of {}", i, level.len(), expected));
+			}
+			levels.push(level.to_vec());
+		}
+
+		Ok(Self {
+			vk_format,
+			width,
+			height,
+			levels,
+		})
+	}
+
+	pub fn to_bytes(&self) -> Vec<u8> {
+		let format = CompressedFormat::from_vk_format(self.vk_format);
+		let dfd = self.data_format_descriptor(format);
+
+		let level_index_size = self.levels.len() * 24;
+		let dfd_offset = LEVEL_INDEX_OFFSET + level_index_size;
+
+		// levels are stored from the smallest one
+		let alignment = format.map_or(4, |format| format.block_bytes());
+		let mut offsets = vec![0; self.levels.len()];
+		let mut end = dfd_offset + dfd.len();
+		for (i, level) in self.levels.iter().enumerate().rev() {
+			end = (end + alignment - 1) / alignment * alignment;
+			offsets[i] = end;
+			end += level.len();
+		}
+
+		let mut out = Vec::with_capacity(end);
+		out.extend_from_slice(&IDENTIFIER);
+		let type_size = 1;
+		for field in [
+			self.vk_format,
+			type_size,
+			self.width,
+			self.height,
+			0,
+			0,
+			1,
+			self.levels.len() as u32,
+			0,
+		]
+		.iter()
+		{
+			out.write_u32::<LittleEndian>(*field).unwrap();
+		}
+
+		out.write_u32::<LittleEndian>(dfd_offset as u32).unwrap();
+		out.write_u32::<LittleEndian>(dfd.len() as u32).unwrap();
+		out.write_u32::<LittleEndian>(0).unwrap();
+		out.write_u32::<LittleEndian>(0).unwrap();
+		out.write_u64::<LittleEndian>(0).unwrap();
+		out.write_u64::<LittleEndian>(0).unwrap();
+
+		for (level, offset) in self.levels.iter().zip(offsets.iter()) {
+			out.write_u64::<LittleEndian>(*offset as u64).unwrap();
+			out.write_u64::<LittleEndian>(level.len() as u64).unwrap();
+			out.write_u64::<LittleEndian>(level.len() as u64).unwrap();
+		}
+
+		out.extend_from_slice(&dfd);
+
+		for (i, level) in self.levels.iter().enumerate().rev() {
+			out.resize(offsets[i], 0);
+			out.extend_from_slice(level);
+		}
+
+		out
+	}
+
+	// basic descriptor block, samples are (channel, bit offset, bit length)
+	fn data_format_descriptor(&self, format: Option<CompressedFormat>) -> Vec<u8> {
+		let (model, block, bytes, samples): (u8, (u8, u8), u8, Vec<(u8, u16, u8)>) = match format {
+			None => (1, (1, 1), 4, vec![(0, 0, 8), (1, 8, 8), (2, 16, 8), (15, 24, 8)]),
+			Some(format) => {
+				let (bw, bh) = format.block_size();
+				let bits = format.block_bytes() as u16 * 8;
+				let samples = match format {
+					CompressedFormat::Bc3 | CompressedFormat::Etc2Rgba => vec![(15, 0, 64), (0, 64, 64)],
+					_ => vec![(0, 0, bits as u8)],
+				};
+				let model = match format {
+					CompressedFormat::Bc1 => 128,
+					CompressedFormat::Bc3 => 130,
+					CompressedFormat::Bc7 => 132,
+					CompressedFormat::Etc2Rgb | CompressedFormat::Etc2Rgba => 161,
+					_ => 162,
+				};
+				(model, (bw as u8, bh as u8), format.block_bytes() as u8, samples)
+			}
+		};
+
+		let block_size = 24 + samples.len() * 16;
+		let mut out = Vec::with_capacity(4 + block_size);
+		out.write_u32::<LittleEndian>((4 + block_size) as u32).unwrap();
+		out.write_u32::<LittleEndian>(0).unwrap();
+		out.write_u32::<LittleEndian>(2 | (block_size as u32) << 16).unwrap();
+		// model, BT709 primaries, linear transfer, straight alpha
+		out.extend_from_slice(&[model, 1, 1, 0]);
+		out.extend_from_slice(&[block.0 - 1, block.1 - 1, 0, 0]);
+		out.extend_from_slice(&[bytes, 0, 0, 0, 0, 0, 0, 0]);
+
+		for (channel, offset, length) in samples {
+			out.write_u16::<LittleEndian>(offset).unwrap();
+			out.push((length as u16 - 1) as u8);
+			out.push(channel);
+			out.write_u32::<LittleEndian>(0).unwrap();
+			out.write_u32::<LittleEndian>(0).unwrap();
+			out.write_u32::<LittleEndian>(if format.is_some() { u32::max_value() } else { 255 }).unwrap();
+		}
+
+		out
+	}
+}
+
+// bytes of a `width` x `height` level, rows of raw formats are tightly packed
+fn level_size(vk_format: u32, width: u32, height: u32) -> Result<usize, String> {
+	if let Some(format) = CompressedFormat::from_vk_format(vk_format) {
+		return Ok(format.level_size(width, height));
+	}
+
+	let channels = match vk_format {
+		Ktx2::VK_R8_UNORM => 1,
+		Ktx2::VK_R8G8_UNORM => 2,
+		Ktx2::VK_R8G8B8_UNORM => 3,
+		Ktx2::VK_R8G8B8A8_UNORM => 4,
+		_ => return Err(format!("unsupported ktx2 format: {}", vk_format)),
+	};
+	Ok(width as usize * height as usize * channels)
+}
@@ -0,0 +1,62 @@
+extern crate specs;
+use self::specs::{Component, VecStorage};
+
+use super::{BufferGeometry, SharedGeometry};
+
+#[derive(Clone)]
+pub struct LodLevel {
+	pub distance: f32,
+	pub geometry: SharedGeometry,
+}
+
+// lower detail levels of the entity geometry, `SharedGeometry` of the entity is used closer than the first level
+#[derive(Clone)]
+pub struct Lod {
+	levels: Vec<LodLevel>,
+}
+
+#[allow(dead_code)]
+impl Lod {
+	pub fn new() -> Self {
+		Self { levels: Vec::new() }
+	}
+
+	pub fn add_level(&mut self, distance: f32, geometry: SharedGeometry) -> &mut Self {
+		let index = self.levels.iter().position(|level| level.distance > distance).unwrap_or(self.levels.len());
+		self.levels.insert(index, LodLevel { distance, geometry });
+		self
+	}
+
+	pub fn get_levels(&self) -> &[LodLevel] {
+		&self.levels
+	}
+
+	// geometry for the view distance, `None` for the entity geometry
+	pub fn get_level(&self, distance: f32) -> Option<&SharedGeometry> {
+		self.levels
+			.iter()
+			.take_while(|level| level.distance <= distance)
+			.last()
+			.map(|level| &level.geometry)
+	}
+
+	// simplified copies of `geometry`, `levels` are pairs of (distance, triangle ratio)
+	pub fn generate(geometry: &BufferGeometry, levels: &[(f32, f32)], max_error: f32) -> Self {
+		// duplicated vertices (e.g. unindexed geometry) aren't shared, collapsing them would tear triangles apart
+		let mut welded = geometry.duplicate();
+		welded.merge_vertices(0.0);
+
+		let triangles = welded.indices.len() / 3;
+		let mut lod = Self::new();
+		for (distance, ratio) in levels {
+			let mut level = welded.duplicate();
+			level.simplify((triangles as f32 * ratio) as usize, max_error);
+			lod.add_level(*distance, SharedGeometry::new(level));
+		}
+		lod
+	}
+}
+
+impl Component for Lod {
+	type Storage = VecStorage<Self>;
+}
@@ -13,6 +13,8 @@ use std::sync::{Arc, LockResult, Mutex, MutexGuard};
 pub struct Material {
 	pub name: String,
 	pub blending: Blending,
+	// rasterize polygons as lines
+	pub wireframe: bool,
 	pub uniforms: HashMap<UniformName, Uniform>,
 	shader_program: Arc<Mutex<ShaderProgram>>,
 }
@@ -68,6 +70,14 @@ impl Material {
 		self.shader_program.lock().unwrap().remove_tag(tag);
 	}
 
+	pub fn set_define(&mut self, name: &str, value: &str) {
+		self.shader_program.lock().unwrap().set_define(name, value);
+	}
+
+	pub fn remove_define(&mut self, name: &str) {
+		self.shader_program.lock().unwrap().remove_define(name);
+	}
+
 	pub fn set_uniform<T: ToUniform>(&mut self, name: UniformName, value: T) {
 		self.uniforms.insert(name, value.to_uniform());
 	}
@@ -77,10 +87,25 @@ impl Material {
 			uniforms: HashMap::new(),
 			name: "".to_string(),
 			blending: Blending::None,
+			wireframe: false,
 			shader_program: Arc::new(Mutex::new(ShaderProgram::new(src.to_string()))),
 		}
 	}
 
+	// material from in-memory glsl, sources get the same definitions as shader files;
+	// `name` stands for the source path in shader errors and warnings
+	pub fn new_inline(name: &str, vertex: &str, fragment: &str, geometry: Option<&str>) -> Self {
+		Self {
+			uniforms: HashMap::new(),
+			name: name.to_string(),
+			blending: Blending::None,
+			wireframe: false,
+			shader_program: Arc::new(Mutex::new(ShaderProgram::new_inline(
+				name, vertex, fragment, geometry,
+			))),
+		}
+	}
+
 	pub fn new_basic(color: Vector4<f32>) -> Self {
 		let mut mat = Material::new("basic");
 		mat.set_uniform(UniformName::Color, color);
@@ -173,12 +198,8 @@ impl Material {
 			position_light,
 		);
 		mat.set_uniform(
-			UniformName::Other("colors[0]".to_string()),
-			Vector3::new(0.0, 1.0, 0.0),
-		);
-		mat.set_uniform(
-			UniformName::Other("colors[1]".to_string()),
-			Vector3::new(0.0, 0.0, 1.0),
+			UniformName::Other("colors".to_string()),
+			vec![Vector3::new(0.0, 1.0, 0.0), Vector3::new(0.0, 0.0, 1.0)],
 		);
 
 		mat
@@ -229,4 +250,8 @@ impl SharedMaterials {
 	pub fn clone_material(&self, index: usize) -> Arc<Mutex<Material>> {
 		self.0[index].clone()
 	}
+
+	pub fn ptr_eq(&self, other: &Self) -> bool {
+		self.0.len() == other.0.len() && self.0.iter().zip(other.0.iter()).all(|(a, b)| Arc::ptr_eq(a, b))
+	}
 }
@@ -0,0 +1,547 @@
+extern crate serde;
+
+use self::serde::{Deserialize, Serialize};
+use math::{Vector, Vector3};
+use std::collections::HashMap;
+
+const CACHE_SIZE: usize = 32;
+const FIFO_SIZE: usize = 16;
+
+// Mesh processing done by the resource-manager build, missing fields take `MeshBake::default()`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct MeshBake {
+	pub vertex_cache: bool,
+	// overdraw pass is skipped when null, so it's always written
+	pub overdraw: Option<f32>,
+	pub vertex_fetch: bool,
+}
+
+impl Default for MeshBake {
+	fn default() -> Self {
+		Self {
+			vertex_cache: true,
+			overdraw: Some(1.05),
+			vertex_fetch: true,
+		}
+	}
+}
+
+struct CacheVertex {
+	cache_pos: Option<usize>,
+	score: f32,
+	// remaining triangles
+	triangles: Vec<usize>,
+}
+
+// Forsyth's linear-speed vertex cache optimization of a triangle list
+pub fn optimize_vertex_cache(indices: &mut [u32]) {
+	let tri_count = indices.len() / 3;
+	if tri_count < 2 {
+		return;
+	}
+
+	let vertex_count = indices.iter().max().map_or(0, |e| *e as usize + 1);
+	let mut vertices: Vec<CacheVertex> = (0..vertex_count)
+		.map(|_| CacheVertex {
+			cache_pos: None,
+			score: 0.0,
+			triangles: Vec::new(),
+		})
+		.collect();
+	for (i, v) in indices[..tri_count * 3].iter().enumerate() {
+		vertices[*v as usize].triangles.push(i / 3);
+	}
+	for vertex in vertices.iter_mut() {
+		vertex.score = vertex_score(vertex.cache_pos, vertex.triangles.len());
+	}
+
+	let triangle = |t: usize| [indices[t * 3] as usize, indices[t * 3 + 1] as usize, indices[t * 3 + 2] as usize];
+	let mut tri_score: Vec<f32> = (0..tri_count)
+		.map(|t| triangle(t).iter().map(|v| vertices[*v].score).sum())
+		.collect();
+	let mut added = vec![false; tri_count];
+	let mut order = Vec::with_capacity(tri_count);
+	let mut cache: Vec<usize> = Vec::with_capacity(CACHE_SIZE + 3);
+	let mut cursor = 0;
+
+	let mut best = (0..tri_count).fold(0, |best, t| if tri_score[t] > tri_score[best] { t } else { best });
+
+	loop {
+		added[best] = true;
+		order.push(best);
+		let tri = triangle(best);
+
+		for v in tri.iter() {
+			let vertex = &mut vertices[*v];
+			if let Some(i) = vertex.triangles.iter().position(|t| *t == best) {
+				vertex.triangles.swap_remove(i);
+			}
+		}
+
+		let mut touched = cache.clone();
+		touched.extend_from_slice(&tri);
+
+		let mut next_cache: Vec<usize> = Vec::with_capacity(CACHE_SIZE + 3);
+		for v in tri.iter().chain(cache.iter()) {
+			if !next_cache.contains(v) {
+				next_cache.push(*v);
+			}
+		}
+		next_cache.truncate(CACHE_SIZE);
+		cache = next_cache;
+
+		// evicted vertices lose their position
+		for v in touched.iter() {
+			vertices[*v].cache_pos = None;
+		}
+		for (i, v) in cache.iter().enumerate() {
+			vertices[*v].cache_pos = Some(i);
+		}
+		for v in touched.iter() {
+			let vertex = &mut vertices[*v];
+			vertex.score = vertex_score(vertex.cache_pos, vertex.triangles.len());
+		}
+
+		let mut next = None;
+		let mut next_score = -1.0;
+		for v in touched.iter() {
+			for t in vertices[*v].triangles.iter() {
+				tri_score[*t] = triangle(*t).iter().map(|v| vertices[*v].score).sum();
+				if tri_score[*t] > next_score {
+					next_score = tri_score[*t];
+					next = Some(*t);
+				}
+			}
+		}
+
+		best = match next {
+			Some(t) => t,
+			None => {
+				while cursor < tri_count && added[cursor] {
+					cursor += 1;
+				}
+				if cursor == tri_count {
+					break;
+				}
+				cursor
+			}
+		};
+	}
+
+	let source: Vec<u32> = indices[..tri_count * 3].to_vec();
+	for (i, t) in order.iter().enumerate() {
+		indices[i * 3..i * 3 + 3].copy_from_slice(&source[t * 3..t * 3 + 3]);
+	}
+}
+
+fn vertex_score(cache_pos: Option<usize>, valence: usize) -> f32 {
+	if valence == 0 {
+		return -1.0;
+	}
+
+	let cache_score = match cache_pos {
+		None => 0.0,
+		Some(pos) if pos < 3 => 0.75,
+		Some(pos) => (1.0 - (pos - 3) as f32 / (CACHE_SIZE - 3) as f32).powf(1.5),
+	};
+	cache_score + 2.0 * (valence as f32).powf(-0.5)
+}
+
+// average cache miss ratio of a FIFO cache, per triangle
+pub fn cache_miss_ratio(indices: &[u32]) -> f32 {
+	let tri_count = indices.len() / 3;
+	if tri_count == 0 {
+		return 0.0;
+	}
+	let mut fifo = Vec::with_capacity(FIFO_SIZE);
+	let misses: usize = indices[..tri_count * 3].iter().map(|v| fifo_access(&mut fifo, *v)).sum();
+	misses as f32 / tri_count as f32
+}
+
+fn fifo_access(fifo: &mut Vec<u32>, v: u32) -> usize {
+	if fifo.contains(&v) {
+		return 0;
+	}
+	if fifo.len() == FIFO_SIZE {
+		fifo.remove(0);
+	}
+	fifo.push(v);
+	1
+}
+
+// Reorders clusters of a cache optimized triangle list so outward facing parts are drawn first,
+// clusters are split where the cache restarts and where their miss ratio stays within
+// `threshold` of the cluster one
+pub fn optimize_overdraw(indices: &mut [u32], positions: &[Vector3<f32>], threshold: f32) {
+	let tri_count = indices.len() / 3;
+	if tri_count < 2 {
+		return;
+	}
+
+	// hard boundaries, every vertex of the triangle misses the cache
+	let mut fifo = Vec::with_capacity(FIFO_SIZE);
+	let mut hard = vec![0];
+	for t in 0..tri_count {
+		let misses: usize = indices[t * 3..t * 3 + 3].iter().map(|v| fifo_access(&mut fifo, *v)).sum();
+		if misses == 3 && t > 0 {
+			hard.push(t);
+		}
+	}
+	hard.push(tri_count);
+
+	let mut clusters = vec![];
+	for range in hard.windows(2) {
+		let (start, end) = (range[0], range[1]);
+		let cluster_ratio = cache_miss_ratio(&indices[start * 3..end * 3]);
+
+		let mut fifo = Vec::with_capacity(FIFO_SIZE);
+		let mut sub_start = start;
+		let mut misses = 0;
+		for t in start..end {
+			misses += indices[t * 3..t * 3 + 3].iter().map(|v| fifo_access(&mut fifo, *v)).sum::<usize>();
+			let count = t + 1 - sub_start;
+			if t + 1 < end && misses as f32 / count as f32 <= cluster_ratio * threshold {
+				clusters.push((sub_start, t + 1));
+				sub_start = t + 1;
+				misses = 0;
+				fifo.clear();
+			}
+		}
+		clusters.push((sub_start, end));
+	}
+
+	let mut mesh_center = Vector3::new_zero();
+	for v in indices[..tri_count * 3].iter() {
+		mesh_center.add(&positions[*v as usize]);
+	}
+	mesh_center.multiply_scalar(1.0 / (tri_count * 3) as f32);
+
+	let mut keyed: Vec<(f32, (usize, usize))> = clusters
+		.into_iter()
+		.map(|(start, end)| {
+			let mut center = Vector3::new_zero();
+			let mut normal = Vector3::new_zero();
+			let mut area_sum = 0.0;
+			for t in start..end {
+				let a = &positions[indices[t * 3] as usize];
+				let b = &positions[indices[t * 3 + 1] as usize];
+				let c = &positions[indices[t * 3 + 2] as usize];
+
+				let mut n = b - a;
+				n.cross(&(c - a));
+				let area = n.length();
+
+				let mut centroid = a.clone();
+				centroid.add(b).add(c).multiply_scalar(area / 3.0);
+				center.add(&centroid);
+				normal.add(&n);
+				area_sum += area;
+			}
+			if area_sum > 0.0 {
+				center.multiply_scalar(1.0 / area_sum);
+			}
+			if !normal.is_zero() {
+				normal.normalize();
+			}
+			let mut offset = &center - &mesh_center;
+			(offset.dot(&normal), (start, end))
+		})
+		.collect();
+
+	keyed.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+	let source: Vec<u32> = indices[..tri_count * 3].to_vec();
+	let mut i = 0;
+	for (_, (start, end)) in keyed {
+		let part = &source[start * 3..end * 3];
+		indices[i..i + part.len()].copy_from_slice(part);
+		i += part.len();
+	}
+}
+
+#[derive(Clone, Copy, Default)]
+struct Quadric {
+	// xx, yy, zz, xy, xz, yz, xw, yw, zw, ww
+	a: [f64; 10],
+	weight: f64,
+}
+
+impl Quadric {
+	fn from_plane(n: [f64; 3], d: f64, weight: f64) -> Self {
+		let [x, y, z] = n;
+		let a = [x * x, y * y, z * z, x * y, x * z, y * z, x * d, y * d, z * d, d * d];
+		Self {
+			a: [
+				a[0] * weight,
+				a[1] * weight,
+				a[2] * weight,
+				a[3] * weight,
+				a[4] * weight,
+				a[5] * weight,
+				a[6] * weight,
+				a[7] * weight,
+				a[8] * weight,
+				a[9] * weight,
+			],
+			weight,
+		}
+	}
+
+	fn add(&mut self, other: &Quadric) {
+		for (a, b) in self.a.iter_mut().zip(other.a.iter()) {
+			*a += b;
+		}
+		self.weight += other.weight;
+	}
+
+	// squared distance, averaged by weight
+	fn error(&self, p: [f64; 3]) -> f64 {
+		let [x, y, z] = p;
+		let a = &self.a;
+		let r = a[0] * x * x + a[1] * y * y + a[2] * z * z
+			+ 2.0 * (a[3] * x * y + a[4] * x * z + a[5] * y * z)
+			+ 2.0 * (a[6] * x + a[7] * y + a[8] * z)
+			+ a[9];
+		if self.weight > 0.0 {
+			r.abs() / self.weight
+		} else {
+			0.0
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum VertexKind {
+	Manifold,
+	// open edge of the mesh, moves along it only
+	Border,
+	// seams, attribute and group boundaries and complex vertices stay in place
+	Locked,
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+	[a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+	[a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+	a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn normalized(a: [f64; 3]) -> ([f64; 3], f64) {
+	let length = dot(a, a).sqrt();
+	if length > 0.0 {
+		([a[0] / length, a[1] / length, a[2] / length], length)
+	} else {
+		(a, 0.0)
+	}
+}
+
+// Quadric error metric simplification by edge collapses onto existing vertices, stops at
+// `target_index_count` or when the next collapse exceeds `target_error` relative to the mesh extent,
+// returns new indices and the reached relative error
+pub fn simplify(
+	indices: &[u32],
+	positions: &[Vector3<f32>],
+	locked: &[bool],
+	target_index_count: usize,
+	target_error: f32,
+) -> (Vec<u32>, f32) {
+	let points: Vec<[f64; 3]> = positions.iter().map(|p| [p.x as f64, p.y as f64, p.z as f64]).collect();
+
+	// vertices at the same position
+	let mut welded = HashMap::new();
+	let pos_id: Vec<usize> = positions
+		.iter()
+		.enumerate()
+		.map(|(i, p)| *welded.entry([p.x.to_bits(), p.y.to_bits(), p.z.to_bits()]).or_insert(i))
+		.collect();
+
+	let mut used = vec![0usize; points.len()];
+	let mut vertices_at = vec![0usize; points.len()];
+	let mut seen = vec![false; points.len()];
+	for i in indices.iter() {
+		let i = *i as usize;
+		used[pos_id[i]] += 1;
+		if !seen[i] {
+			seen[i] = true;
+			vertices_at[pos_id[i]] += 1;
+		}
+	}
+
+	let mut edges: HashMap<(usize, usize), usize> = HashMap::new();
+	for tri in indices.chunks(3).filter(|tri| tri.len() == 3) {
+		for k in 0..3 {
+			let (a, b) = (pos_id[tri[k] as usize], pos_id[tri[(k + 1) % 3] as usize]);
+			*edges.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+		}
+	}
+	let is_border = |a: usize, b: usize| edges.get(&(a.min(b), a.max(b))) == Some(&1);
+
+	let mut border_edges = vec![0usize; points.len()];
+	let mut complex = vec![false; points.len()];
+	for ((a, b), count) in edges.iter() {
+		match count {
+			1 => {
+				border_edges[*a] += 1;
+				border_edges[*b] += 1;
+			}
+			2 => {}
+			_ => {
+				complex[*a] = true;
+				complex[*b] = true;
+			}
+		}
+	}
+
+	let kinds: Vec<VertexKind> = (0..points.len())
+		.map(|i| {
+			let p = pos_id[i];
+			if locked.get(i).cloned().unwrap_or(false) || vertices_at[p] > 1 || complex[p] {
+				VertexKind::Locked
+			} else if border_edges[p] == 0 {
+				VertexKind::Manifold
+			} else if border_edges[p] == 2 {
+				VertexKind::Border
+			} else {
+				VertexKind::Locked
+			}
+		})
+		.collect();
+	// any locked vertex locks its position
+	let mut position_locked = vec![false; points.len()];
+	for i in 0..points.len() {
+		if kinds[i] == VertexKind::Locked {
+			position_locked[pos_id[i]] = true;
+		}
+	}
+
+	let mut quadrics = vec![Quadric::default(); points.len()];
+	for tri in indices.chunks(3).filter(|tri| tri.len() == 3) {
+		let (a, b, c) = (tri[0] as usize, tri[1] as usize, tri[2] as usize);
+		let (normal, area) = normalized(cross(sub(points[b], points[a]), sub(points[c], points[a])));
+		if area == 0.0 {
+			continue;
+		}
+		let plane = Quadric::from_plane(normal, -dot(normal, points[a]), area);
+		for v in [a, b, c].iter() {
+			quadrics[pos_id[*v]].add(&plane);
+		}
+
+		for k in 0..3 {
+			let (v0, v1) = (tri[k] as usize, tri[(k + 1) % 3] as usize);
+			if !is_border(pos_id[v0], pos_id[v1]) {
+				continue;
+			}
+			let (edge, length) = normalized(sub(points[v1], points[v0]));
+			let (side, _) = normalized(cross(edge, normal));
+			let plane = Quadric::from_plane(side, -dot(side, points[v0]), length * 10.0);
+			quadrics[pos_id[v0]].add(&plane);
+			quadrics[pos_id[v1]].add(&plane);
+		}
+	}
+
+	let (min, max) = points.iter().fold(([std::f64::MAX; 3], [std::f64::MIN; 3]), |(mut min, mut max), p| {
+		for k in 0..3 {
+			min[k] = min[k].min(p[k]);
+			max[k] = max[k].max(p[k]);
+		}
+		(min, max)
+	});
+	let scale = (0..3).map(|k| max[k] - min[k]).fold(0.0, f64::max).max(std::f64::EPSILON);
+	let limit = (target_error as f64 * scale).powi(2);
+
+	let mut indices: Vec<u32> = indices[..indices.len() / 3 * 3].to_vec();
+	let mut result_error = 0.0f64;
+
+	while indices.len() > target_index_count {
+		let mut adjacency = vec![Vec::new(); points.len()];
+		for (t, tri) in indices.chunks(3).enumerate() {
+			for v in tri.iter() {
+				adjacency[*v as usize].push(t);
+			}
+		}
+
+		let mut candidates = Vec::new();
+		for tri in indices.chunks(3) {
+			for k in 0..3 {
+				for &(u, v) in [(tri[k], tri[(k + 1) % 3]), (tri[(k + 1) % 3], tri[k])].iter() {
+					let (u, v) = (u as usize, v as usize);
+					let allowed = match kinds[u] {
+						VertexKind::Manifold => true,
+						VertexKind::Border => kinds[v] != VertexKind::Manifold && is_border(pos_id[u], pos_id[v]),
+						VertexKind::Locked => false,
+					};
+					if allowed && !position_locked[pos_id[u]] && pos_id[u] != pos_id[v] {
+						candidates.push((quadrics[pos_id[u]].error(points[v]), u, v));
+					}
+				}
+			}
+		}
+		candidates.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+		// each collapse removes about two triangles
+		let budget = (indices.len() - target_index_count) / 6 + 1;
+		let mut remap: Vec<usize> = (0..points.len()).collect();
+		let mut touched = vec![false; points.len()];
+		let mut collapsed = 0;
+
+		for (cost, u, v) in candidates {
+			if collapsed >= budget || cost > limit {
+				break;
+			}
+			if touched[pos_id[u]] || touched[pos_id[v]] {
+				continue;
+			}
+
+			let flips = adjacency[u].iter().any(|t| {
+				let tri = &indices[*t * 3..*t * 3 + 3];
+				if tri.contains(&(v as u32)) {
+					return false;
+				}
+				let corners: Vec<[f64; 3]> = tri.iter().map(|e| points[*e as usize]).collect();
+				let moved: Vec<[f64; 3]> = tri
+					.iter()
+					.map(|e| if *e as usize == u { points[v] } else { points[*e as usize] })
+					.collect();
+				let before = cross(sub(corners[1], corners[0]), sub(corners[2], corners[0]));
+				let after = cross(sub(moved[1], moved[0]), sub(moved[2], moved[0]));
+				dot(before, after) <= 0.0
+			});
+			if flips {
+				continue;
+			}
+
+			remap[u] = v;
+			let quadric = quadrics[pos_id[u]];
+			quadrics[pos_id[v]].add(&quadric);
+			for t in adjacency[u].iter() {
+				for e in indices[*t * 3..*t * 3 + 3].iter() {
+					touched[pos_id[*e as usize]] = true;
+				}
+			}
+			result_error = result_error.max(cost);
+			collapsed += 1;
+		}
+
+		if collapsed == 0 {
+			break;
+		}
+
+		let mut next = Vec::with_capacity(indices.len());
+		for tri in indices.chunks(3) {
+			let tri = [remap[tri[0] as usize], remap[tri[1] as usize], remap[tri[2] as usize]];
+			let (a, b, c) = (pos_id[tri[0]], pos_id[tri[1]], pos_id[tri[2]]);
+			if a != b && b != c && a != c {
+				next.extend(tri.iter().map(|e| *e as u32));
+			}
+		}
+		indices = next;
+	}
+
+	(indices, (result_error.sqrt() / scale) as f32)
+}
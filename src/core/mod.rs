@@ -5,12 +5,19 @@ mod transform;
 mod perspective_camera;
 mod light;
 mod shader_program;
+mod shader_reflection;
 mod boundings;
 mod world;
 mod systems;
 mod relation;
 mod frame_buffer;
 mod render_buffer;
+mod texture_compression;
+mod ktx2;
+mod mesh_optimizer;
+mod lod;
+mod batching;
+mod instances;
 
 
 pub use self::buffer_geometry::*;
@@ -20,9 +27,16 @@ pub use self::transform::*;
 pub use self::perspective_camera::*;
 pub use self::light::*;
 pub use self::shader_program::*;
+pub use self::shader_reflection::*;
 pub use self::boundings::*;
 pub use self::world::create_world;
 pub use self::systems::*;
 pub use self::relation::*;
 pub use self::frame_buffer::*;
 pub use self::render_buffer::*;
+pub use self::texture_compression::*;
+pub use self::ktx2::*;
+pub use self::mesh_optimizer::*;
+pub use self::lod::*;
+pub use self::batching::*;
+pub use self::instances::*;
@@ -136,8 +136,8 @@ impl EntityRelations for World {
 	}
 
 	fn remove_recursive(&mut self, elem: Entity) -> Result<(), WrongGeneration> {
-		let mut items = self.get_all_children_entities(elem);
-		items.push(elem);
+		// includes `elem`
+		let items = self.get_all_children_entities(elem);
 		self.delete_entities(&items)
 	}
 }
@@ -1,11 +1,13 @@
 extern crate uuid;
 use self::uuid::Uuid;
 
+extern crate serde;
 extern crate specs;
 
+use self::serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
-use super::SharedTexture2D;
+use super::{ShaderReflection, SharedTexture2D};
 
 use math::{Matrix3, Matrix4, Vector2, Vector3, Vector4};
 
@@ -20,7 +22,28 @@ pub enum Uniform {
 	Float(f32),
 	Int(i32),
 	UInt(u32),
+	Bool(bool),
+	IVec2([i32; 2]),
+	IVec3([i32; 3]),
+	IVec4([i32; 4]),
+	UVec2([u32; 2]),
+	UVec3([u32; 3]),
+	UVec4([u32; 4]),
 	Texture2D(Option<SharedTexture2D>, u32),
+
+	FloatArray(Vec<f32>),
+	IntArray(Vec<i32>),
+	UIntArray(Vec<u32>),
+	Vector2Array(Vec<Vector2<f32>>),
+	Vector3Array(Vec<Vector3<f32>>),
+	Vector4Array(Vec<Vector4<f32>>),
+	Matrix3Array(Vec<Matrix3<f32>>),
+	Matrix4Array(Vec<Matrix4<f32>>),
+
+	// members are set as "name.member"
+	Struct(Vec<(String, Uniform)>),
+	// items are set as "name[i].member"
+	StructArray(Vec<Vec<(String, Uniform)>>),
 }
 
 #[derive(Debug, Clone, PartialEq, Hash, Eq)]
@@ -86,17 +109,57 @@ impl UniformName {
 			UniformName::Other(value) => value.clone(),
 		}
 	}
+
+	pub fn from_name(name: &str) -> Self {
+		match name {
+			"color" => UniformName::Color,
+			"normal" => UniformName::Normal,
+			"normal_scale" => UniformName::NormalScale,
+			"specular" => UniformName::Specular,
+			"specular_strength" => UniformName::SpecularStrength,
+			"emissive" => UniformName::Emissive,
+			"time" => UniformName::Time,
+			"alpha" => UniformName::Alpha,
+			"roughness" => UniformName::Roughness,
+			"metalness" => UniformName::Metalness,
+			"ambient_light" => UniformName::AmbientLight,
+
+			"map_color" => UniformName::MapColor,
+			"map_specular" => UniformName::MapSpecular,
+			"map_roughness" => UniformName::MapRoughness,
+			"map_normal" => UniformName::MapNormal,
+			"map_metalness" => UniformName::MapMetalness,
+			"map_emissive" => UniformName::MapEmissive,
+			"map_occlusion" => UniformName::MapOcclusion,
+			"map_alpha" => UniformName::MapAlpha,
+
+			"matrix_model" => UniformName::MatrixModel,
+			"matrix_view" => UniformName::MatrixView,
+			"matrix_normal" => UniformName::MatrixNormal,
+			"shininess" => UniformName::Shininess,
+
+			_ => UniformName::Other(name.to_string()),
+		}
+	}
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Hash, Copy)]
+#[serde(rename_all = "snake_case")]
 pub enum Blending {
 	None,
 	Mix,
 	Additive,
 }
 
+impl Default for Blending {
+	fn default() -> Self {
+		Blending::None
+	}
+}
+
 #[allow(non_camel_case_types)]
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
 pub enum ShaderTag {
 	Lighting,
 	Metalness,
@@ -208,6 +271,96 @@ impl ToUniform for u32 {
 		Uniform::UInt(self)
 	}
 }
+impl ToUniform for bool {
+	fn to_uniform(self) -> Uniform {
+		Uniform::Bool(self)
+	}
+}
+impl ToUniform for [i32; 2] {
+	fn to_uniform(self) -> Uniform {
+		Uniform::IVec2(self)
+	}
+}
+impl ToUniform for [i32; 3] {
+	fn to_uniform(self) -> Uniform {
+		Uniform::IVec3(self)
+	}
+}
+impl ToUniform for [i32; 4] {
+	fn to_uniform(self) -> Uniform {
+		Uniform::IVec4(self)
+	}
+}
+impl ToUniform for [u32; 2] {
+	fn to_uniform(self) -> Uniform {
+		Uniform::UVec2(self)
+	}
+}
+impl ToUniform for [u32; 3] {
+	fn to_uniform(self) -> Uniform {
+		Uniform::UVec3(self)
+	}
+}
+impl ToUniform for [u32; 4] {
+	fn to_uniform(self) -> Uniform {
+		Uniform::UVec4(self)
+	}
+}
+impl ToUniform for Vec<f32> {
+	fn to_uniform(self) -> Uniform {
+		Uniform::FloatArray(self)
+	}
+}
+impl ToUniform for Vec<i32> {
+	fn to_uniform(self) -> Uniform {
+		Uniform::IntArray(self)
+	}
+}
+impl ToUniform for Vec<u32> {
+	fn to_uniform(self) -> Uniform {
+		Uniform::UIntArray(self)
+	}
+}
+impl ToUniform for Vec<Vector2<f32>> {
+	fn to_uniform(self) -> Uniform {
+		Uniform::Vector2Array(self)
+	}
+}
+impl ToUniform for Vec<Vector3<f32>> {
+	fn to_uniform(self) -> Uniform {
+		Uniform::Vector3Array(self)
+	}
+}
+impl ToUniform for Vec<Vector4<f32>> {
+	fn to_uniform(self) -> Uniform {
+		Uniform::Vector4Array(self)
+	}
+}
+impl ToUniform for Vec<Matrix3<f32>> {
+	fn to_uniform(self) -> Uniform {
+		Uniform::Matrix3Array(self)
+	}
+}
+impl ToUniform for Vec<Matrix4<f32>> {
+	fn to_uniform(self) -> Uniform {
+		Uniform::Matrix4Array(self)
+	}
+}
+impl ToUniform for Vec<(String, Uniform)> {
+	fn to_uniform(self) -> Uniform {
+		Uniform::Struct(self)
+	}
+}
+impl ToUniform for Vec<Vec<(String, Uniform)>> {
+	fn to_uniform(self) -> Uniform {
+		Uniform::StructArray(self)
+	}
+}
+impl ToUniform for Uniform {
+	fn to_uniform(self) -> Uniform {
+		self
+	}
+}
 impl ToUniform for (Option<SharedTexture2D>, u32) {
 	fn to_uniform(self) -> Uniform {
 		Uniform::Texture2D(self.0, self.1)
@@ -224,24 +377,53 @@ impl ToUniform for SharedTexture2D {
 	}
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShaderSource {
+	// name of `res/shaders/<name>.glsl` with `#<vertex>` / `#<fragment>` markers
+	File(String),
+	Inline {
+		vertex: String,
+		fragment: String,
+		geometry: Option<String>,
+	},
+}
 
 #[derive(Debug)]
 pub struct ShaderProgram {
 	uuid: Uuid,
 	src: String,
+	source: ShaderSource,
 	uniforms: HashMap<UniformName, Uniform>,
 	tags: HashSet<ShaderTag>,
+	defines: HashMap<String, String>,
+	reflection: Option<ShaderReflection>,
 	need_update: bool,
 	// blending_mode: Blending,
 }
 
 impl ShaderProgram {
 	pub fn new(src: String) -> Self {
+		Self::new_with_source(src.clone(), ShaderSource::File(src))
+	}
+
+	pub fn new_inline(name: &str, vertex: &str, fragment: &str, geometry: Option<&str>) -> Self {
+		let source = ShaderSource::Inline {
+			vertex: vertex.to_string(),
+			fragment: fragment.to_string(),
+			geometry: geometry.map(|e| e.to_string()),
+		};
+		Self::new_with_source(name.to_string(), source)
+	}
+
+	fn new_with_source(src: String, source: ShaderSource) -> Self {
 		Self {
 			uuid: Uuid::new_v4(),
-			src: src.to_string(),
+			src,
+			source,
 			uniforms: HashMap::default(),
 			tags: HashSet::new(),
+			defines: HashMap::new(),
+			reflection: None,
 			need_update: true,
 			// blending_mode: Blending::None,
 		}
@@ -279,6 +461,33 @@ impl ShaderProgram {
 		&mut self.tags
 	}
 
+	pub fn set_define(&mut self, name: &str, value: &str) {
+		if self.defines.get(name).map_or(false, |e| e == value) {
+			return;
+		}
+		self.defines.insert(name.to_string(), value.to_string());
+		self.set_need_update(true);
+	}
+
+	pub fn remove_define(&mut self, name: &str) {
+		if self.defines.remove(name).is_some() {
+			self.set_need_update(true);
+		}
+	}
+
+	pub fn get_defines(&self) -> &HashMap<String, String> {
+		&self.defines
+	}
+
+	// filled by the renderer after the program is linked
+	pub fn get_reflection(&self) -> Option<&ShaderReflection> {
+		self.reflection.as_ref()
+	}
+
+	pub fn set_reflection(&mut self, reflection: Option<ShaderReflection>) {
+		self.reflection = reflection;
+	}
+
 	pub fn get_uuid(&self) -> Uuid {
 		self.uuid
 	}
@@ -306,6 +515,10 @@ impl ShaderProgram {
 		&self.src[..]
 	}
 
+	pub fn get_source(&self) -> &ShaderSource {
+		&self.source
+	}
+
 	pub fn get_uniforms(&self) -> &HashMap<UniformName, Uniform> {
 		&self.uniforms
 	}
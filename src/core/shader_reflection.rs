@@ -0,0 +1,194 @@
+use super::{BufferGeometry, Uniform, UniformName, INSTANCE_ATTRIBUTE_LOCATION};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShaderDataType {
+	Float,
+	Vec2,
+	Vec3,
+	Vec4,
+	Int,
+	IVec2,
+	IVec3,
+	IVec4,
+	UInt,
+	UVec2,
+	UVec3,
+	UVec4,
+	Bool,
+	Mat2,
+	Mat3,
+	Mat4,
+	Sampler2D,
+	Other(u32),
+}
+
+impl ShaderDataType {
+	pub fn components(&self) -> usize {
+		match self {
+			ShaderDataType::Float => 1,
+			ShaderDataType::Vec2 => 2,
+			ShaderDataType::Vec3 => 3,
+			ShaderDataType::Vec4 => 4,
+			ShaderDataType::Int => 1,
+			ShaderDataType::IVec2 => 2,
+			ShaderDataType::IVec3 => 3,
+			ShaderDataType::IVec4 => 4,
+			ShaderDataType::UInt => 1,
+			ShaderDataType::UVec2 => 2,
+			ShaderDataType::UVec3 => 3,
+			ShaderDataType::UVec4 => 4,
+			ShaderDataType::Bool => 1,
+			ShaderDataType::Mat2 => 4,
+			ShaderDataType::Mat3 => 9,
+			ShaderDataType::Mat4 => 16,
+			ShaderDataType::Sampler2D => 1,
+			ShaderDataType::Other(_) => 0,
+		}
+	}
+
+	pub fn from_uniform(uniform: &Uniform) -> Option<Self> {
+		match uniform {
+			Uniform::Vector2(_) | Uniform::Vector2Array(_) => Some(ShaderDataType::Vec2),
+			Uniform::Vector3(_) | Uniform::Vector3Array(_) => Some(ShaderDataType::Vec3),
+			Uniform::Vector4(_) | Uniform::Vector4Array(_) => Some(ShaderDataType::Vec4),
+			Uniform::Matrix3(_) | Uniform::Matrix3Array(_) => Some(ShaderDataType::Mat3),
+			Uniform::Matrix4(_) | Uniform::Matrix4Array(_) => Some(ShaderDataType::Mat4),
+			Uniform::Float(_) | Uniform::FloatArray(_) => Some(ShaderDataType::Float),
+			Uniform::Int(_) | Uniform::IntArray(_) => Some(ShaderDataType::Int),
+			Uniform::UInt(_) | Uniform::UIntArray(_) => Some(ShaderDataType::UInt),
+			Uniform::Bool(_) => Some(ShaderDataType::Bool),
+			Uniform::IVec2(_) => Some(ShaderDataType::IVec2),
+			Uniform::IVec3(_) => Some(ShaderDataType::IVec3),
+			Uniform::IVec4(_) => Some(ShaderDataType::IVec4),
+			Uniform::UVec2(_) => Some(ShaderDataType::UVec2),
+			Uniform::UVec3(_) => Some(ShaderDataType::UVec3),
+			Uniform::UVec4(_) => Some(ShaderDataType::UVec4),
+			Uniform::Texture2D(_, _) => Some(ShaderDataType::Sampler2D),
+			Uniform::Struct(_) | Uniform::StructArray(_) => None,
+		}
+	}
+
+	// bool and sampler uniforms are set with glUniform1i
+	pub fn is_compatible(&self, other: &ShaderDataType) -> bool {
+		match (self, other) {
+			(a, b) if a == b => true,
+			(ShaderDataType::Int, ShaderDataType::Bool) => true,
+			(ShaderDataType::Int, ShaderDataType::Sampler2D) => true,
+			_ => false,
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveUniform {
+	pub name: String,
+	pub location: i32,
+	pub size: i32,
+	pub data_type: ShaderDataType,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveAttribute {
+	pub name: String,
+	pub location: i32,
+	pub size: i32,
+	pub data_type: ShaderDataType,
+}
+
+// Active uniforms (outside of uniform blocks) and vertex attributes of a linked program
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ShaderReflection {
+	pub uniforms: Vec<ActiveUniform>,
+	pub attributes: Vec<ActiveAttribute>,
+}
+
+impl ShaderReflection {
+	pub fn get_uniform(&self, name: &str) -> Option<&ActiveUniform> {
+		self.uniforms
+			.iter()
+			.find(|e| e.name == name || e.name.trim_end_matches("[0]") == name)
+	}
+
+	pub fn get_attribute(&self, name: &str) -> Option<&ActiveAttribute> {
+		self.attributes.iter().find(|e| e.name == name)
+	}
+
+	pub fn validate(&self, uniforms: &HashMap<UniformName, Uniform>, geometry: &BufferGeometry) -> Vec<String> {
+		let mut errors = Vec::new();
+
+		let mut flat = Vec::new();
+		uniforms
+			.iter()
+			.filter(|(name, _)| !is_renderer_uniform(name))
+			.for_each(|(name, uniform)| flatten_uniform(name.get_name(), uniform, &mut flat));
+
+		flat.iter().for_each(|(name, uniform)| {
+			let expected = match ShaderDataType::from_uniform(uniform) {
+				None => return,
+				Some(data_type) => data_type,
+			};
+
+			match self.get_uniform(name) {
+				None => errors.push(format!("uniform '{}' is not active in program", name)),
+				Some(active) => {
+					if !expected.is_compatible(&active.data_type) {
+						errors.push(format!(
+							"uniform '{}' type mismatch: material {:?}, program {:?}",
+							name, expected, active.data_type
+						));
+					}
+				}
+			}
+		});
+
+		// attribute locations follow the order of BufferGeometry::iter_attributes, instance data comes after
+		let buffers: Vec<_> = geometry.iter_attributes().collect();
+		self.attributes
+			.iter()
+			.filter(|e| e.location >= 0 && (e.location as usize) < INSTANCE_ATTRIBUTE_LOCATION)
+			.for_each(|attribute| {
+				match buffers.get(attribute.location as usize) {
+					None => errors.push(format!(
+						"attribute '{}' (location {}) is missing in geometry '{}'",
+						attribute.name, attribute.location, geometry.name
+					)),
+					Some(buffer) => {
+						if buffer.item_size() != attribute.data_type.components() {
+							errors.push(format!(
+								"attribute '{}' (location {}) expects {:?}, geometry has {:?} with {} components",
+								attribute.name,
+								attribute.location,
+								attribute.data_type,
+								buffer.buffer_type,
+								buffer.item_size()
+							));
+						}
+					}
+				}
+			});
+
+		errors
+	}
+}
+
+fn is_renderer_uniform(name: &UniformName) -> bool {
+	match name {
+		UniformName::MatrixModel | UniformName::MatrixView | UniformName::MatrixNormal | UniformName::Time => true,
+		_ => false,
+	}
+}
+
+fn flatten_uniform<'a>(name: String, uniform: &'a Uniform, res: &mut Vec<(String, &'a Uniform)>) {
+	match uniform {
+		Uniform::Struct(members) => members
+			.iter()
+			.for_each(|(member, uniform)| flatten_uniform(format!("{}.{}", name, member), uniform, res)),
+		Uniform::StructArray(items) => items.iter().enumerate().for_each(|(i, members)| {
+			members
+				.iter()
+				.for_each(|(member, uniform)| flatten_uniform(format!("{}[{}].{}", name, i, member), uniform, res))
+		}),
+		_ => res.push((name, uniform)),
+	}
+}
@@ -3,6 +3,7 @@ extern crate uuid;
 
 use self::image::{ColorType, GenericImageView};
 use self::uuid::Uuid;
+use super::{CompressedFormat, Ktx2};
 use std::path::Path;
 use std::sync::{Arc, LockResult, Mutex, MutexGuard};
 
@@ -47,6 +48,9 @@ pub enum TextureColorType {
 #[derive(Debug, Clone, PartialEq)]
 pub enum TextureDataSource {
 	Raw(Vec<u8>),
+	// baked mip chain, level 0 first
+	Mipmaps(Vec<Vec<u8>>),
+	Compressed(CompressedFormat, Vec<Vec<u8>>),
 	RawUploaded,
 	TextureBuffer,
 }
@@ -63,6 +67,7 @@ pub struct Texture2D {
 	pub min_filter: MinFilter,
 	pub auto_clear_texture_data: bool,
 	pub need_update: bool, // TODO: UPDATE
+	pub pending: bool, // data is being loaded in background
 	texture_data: Option<TextureData>,
 }
 
@@ -74,6 +79,87 @@ pub struct TextureData {
 	pub data: TextureDataSource, // TODO optional data for memory save
 }
 
+impl TextureData {
+	pub fn from_path(path: &str) -> Result<Self, String> {
+		if path.ends_with(".ktx2") {
+			let data = std::fs::read(path).or(Err(format!("cant open image: {}", path)))?;
+			return Self::from_ktx2(&data);
+		}
+
+		let img = match image::open(&Path::new(path)) {
+			Err(_) => {
+				return Err(format!("cant open image: {}", path));
+			}
+			Ok(im) => im.flipv(),
+		};
+
+		let color_type = img.color().into();
+
+		let data = img.raw_pixels();
+		let (width, height) = img.dimensions();
+
+		Ok(TextureData {
+			data: TextureDataSource::Raw(data),
+			width,
+			height,
+			color_type,
+		})
+	}
+
+	pub fn from_ktx2(data: &[u8]) -> Result<Self, String> {
+		let Ktx2 {
+			vk_format,
+			width,
+			height,
+			mut levels,
+		} = Ktx2::parse(data)?;
+
+		if let Some(format) = CompressedFormat::from_vk_format(vk_format) {
+			let color_type = match format.has_alpha() {
+				true => TextureColorType::RGBA(8),
+				false => TextureColorType::RGB(8),
+			};
+			return Ok(TextureData {
+				color_type,
+				width,
+				height,
+				data: TextureDataSource::Compressed(format, levels),
+			});
+		}
+
+		let color_type = match vk_format {
+			Ktx2::VK_R8_UNORM => TextureColorType::R(8),
+			Ktx2::VK_R8G8_UNORM => TextureColorType::RG(8),
+			Ktx2::VK_R8G8B8_UNORM => TextureColorType::RGB(8),
+			Ktx2::VK_R8G8B8A8_UNORM => TextureColorType::RGBA(8),
+			_ => return Err(format!("unsupported ktx2 format: {}", vk_format)),
+		};
+
+		let data = match levels.len() {
+			1 => TextureDataSource::Raw(levels.remove(0)),
+			_ => TextureDataSource::Mipmaps(levels),
+		};
+
+		Ok(TextureData {
+			color_type,
+			width,
+			height,
+			data,
+		})
+	}
+
+	// CPU side bytes, uploaded data is cleared by `auto_clear_texture_data`
+	pub fn memory_size(&self) -> usize {
+		match &self.data {
+			TextureDataSource::Raw(data) => data.len(),
+			TextureDataSource::Mipmaps(levels) | TextureDataSource::Compressed(_, levels) => {
+				levels.iter().map(|level| level.len()).sum()
+			}
+			_ => 0,
+		}
+	}
+}
+
 impl Texture2D {
 	pub fn new(path: &str) -> Self {
 		let mut e = Self::default();
@@ -88,7 +174,17 @@ impl Texture2D {
 	}
 
 	pub fn new_from_bytes(bytes: &[u8]) -> Self {
-		let img = image::load_from_memory(bytes).unwrap();
+		Self::from_bytes(bytes).unwrap()
+	}
+
+	pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+		if Ktx2::is_ktx2(bytes) {
+			let mut e = Self::default();
+			e.texture_data = Some(TextureData::from_ktx2(bytes)?);
+			return Ok(e);
+		}
+
+		let img = image::load_from_memory(bytes).or_else(|e| Err(format!("cant decode image: {}", e)))?;
 
 		let color_type = img.color().into();
 
@@ -102,32 +198,14 @@ impl Texture2D {
 			height,
 			color_type,
 		});
-		e
+		Ok(e)
 	}
 
 	pub fn load(&mut self) -> Result<&TextureData, (String)> {
 		match (&self.path, self.texture_data.is_none()) {
 			(_, false) => Ok(self.texture_data.as_ref().unwrap()),
 			(Some(path), true) => {
-				let img = match image::open(&Path::new(path)) {
-					Err(_) => {
-						return Err(format!("cant open image: {}", path));
-					}
-					Ok(im) => im.flipv(),
-				};
-
-				let color_type = img.color().into();
-
-				let data = img.raw_pixels();
-				let (width, height) = img.dimensions();
-
-				self.texture_data = Some(TextureData {
-					data: TextureDataSource::Raw(data),
-					width,
-					height,
-					color_type,
-				});
-
+				self.texture_data = Some(TextureData::from_path(path)?);
 				Ok(self.texture_data.as_ref().unwrap())
 			}
 			_ => Err("missing path for load image".to_string()),
@@ -157,6 +235,12 @@ impl Texture2D {
 					texture_data.width = width;
 					texture_data.height = height;
 				}
+				// baked levels don't match the new size, the texture becomes an empty buffer
+				TextureDataSource::Mipmaps(_) | TextureDataSource::Compressed(_, _) => {
+					texture_data.data = TextureDataSource::TextureBuffer;
+					texture_data.width = width;
+					texture_data.height = height;
+				}
 				TextureDataSource::Raw(_) => unimplemented!(),
 				TextureDataSource::RawUploaded => unimplemented!(),
 			}
@@ -201,6 +285,7 @@ impl SharedTexture2D {
 			mag_filter: MagFilter::Nearest,
 			auto_clear_texture_data: false,
 			need_update: true,
+			pending: false,
 			texture_data: Some(data),
 		};
 
@@ -224,6 +309,7 @@ impl SharedTexture2D {
 			mag_filter: MagFilter::Nearest,
 			auto_clear_texture_data: false,
 			need_update: true,
+			pending: false,
 			texture_data: Some(data),
 		};
 
@@ -237,6 +323,10 @@ impl SharedTexture2D {
 	pub fn get_uuid(&self) -> Uuid {
 		self.uuid
 	}
+
+	pub fn ref_count(&self) -> usize {
+		Arc::strong_count(&self.data)
+	}
 }
 
 impl Default for Texture2D {
@@ -250,6 +340,7 @@ impl Default for Texture2D {
 			mag_filter: MagFilter::Linear,
 			auto_clear_texture_data: true,
 			need_update: true,
+			pending: false,
 			texture_data: None,
 		}
 	}
@@ -0,0 +1,537 @@
+extern crate image;
+extern crate serde;
+
+use self::serde::{Deserialize, Serialize};
+use super::Ktx2;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CompressedFormat {
+	Bc1,
+	Bc3,
+	Bc7,
+	Etc2Rgb,
+	Etc2Rgba,
+	Astc4x4,
+	Astc6x6,
+	Astc8x8,
+}
+
+// Texture processing done by the resource-manager build, result is stored as KTX2
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct TextureBake {
+	#[serde(default)]
+	pub mipmaps: bool,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub format: Option<CompressedFormat>,
+}
+
+impl CompressedFormat {
+	pub fn from_name(name: &str) -> Result<Self, String> {
+		match name {
+			"bc1" => Ok(CompressedFormat::Bc1),
+			"bc3" => Ok(CompressedFormat::Bc3),
+			"bc7" => Ok(CompressedFormat::Bc7),
+			"etc2_rgb" => Ok(CompressedFormat::Etc2Rgb),
+			"etc2_rgba" => Ok(CompressedFormat::Etc2Rgba),
+			"astc4x4" => Ok(CompressedFormat::Astc4x4),
+			"astc6x6" => Ok(CompressedFormat::Astc6x6),
+			"astc8x8" => Ok(CompressedFormat::Astc8x8),
+			_ => Err(format!("unknown texture format: {}", name)),
+		}
+	}
+
+	pub fn block_size(&self) -> (u32, u32) {
+		match self {
+			CompressedFormat::Astc6x6 => (6, 6),
+			CompressedFormat::Astc8x8 => (8, 8),
+			_ => (4, 4),
+		}
+	}
+
+	pub fn block_bytes(&self) -> usize {
+		match self {
+			CompressedFormat::Bc1 | CompressedFormat::Etc2Rgb => 8,
+			_ => 16,
+		}
+	}
+
+	pub fn has_alpha(&self) -> bool {
+		match self {
+			CompressedFormat::Bc1 | CompressedFormat::Etc2Rgb => false,
+			_ => true,
+		}
+	}
+
+	// BC7 and ASTC textures are uploaded as is, they are encoded by external tools
+	pub fn can_encode(&self) -> bool {
+		match self {
+			CompressedFormat::Bc1 | CompressedFormat::Bc3 | CompressedFormat::Etc2Rgb | CompressedFormat::Etc2Rgba => true,
+			_ => false,
+		}
+	}
+
+	pub fn level_size(&self, width: u32, height: u32) -> usize {
+		let (bw, bh) = self.block_size();
+		let (width, height, bw, bh) = (width as usize, height as usize, bw as usize, bh as usize);
+		((width + bw - 1) / bw) * ((height + bh - 1) / bh) * self.block_bytes()
+	}
+
+	pub fn vk_format(&self) -> u32 {
+		match self {
+			CompressedFormat::Bc1 => 131,
+			CompressedFormat::Bc3 => 137,
+			CompressedFormat::Bc7 => 145,
+			CompressedFormat::Etc2Rgb => 147,
+			CompressedFormat::Etc2Rgba => 151,
+			CompressedFormat::Astc4x4 => 157,
+			CompressedFormat::Astc6x6 => 165,
+			CompressedFormat::Astc8x8 => 171,
+		}
+	}
+
+	pub fn from_vk_format(vk_format: u32) -> Option<Self> {
+		match vk_format {
+			131 => Some(CompressedFormat::Bc1),
+			137 => Some(CompressedFormat::Bc3),
+			145 => Some(CompressedFormat::Bc7),
+			147 => Some(CompressedFormat::Etc2Rgb),
+			151 => Some(CompressedFormat::Etc2Rgba),
+			157 => Some(CompressedFormat::Astc4x4),
+			165 => Some(CompressedFormat::Astc6x6),
+			171 => Some(CompressedFormat::Astc8x8),
+			_ => None,
+		}
+	}
+}
+
+// decodes an image and stores it as KTX2 with optional mip chain and compression
+pub fn bake_texture(data: &[u8], bake: &TextureBake) -> Result<Vec<u8>, String> {
+	let img = image::load_from_memory(data).or_else(|e| Err(format!("cant decode image: {}", e)))?;
+	let img = img.to_rgba();
+	let (width, height) = img.dimensions();
+	let pixels = img.into_raw();
+
+	let levels = match bake.mipmaps {
+		true => generate_mipmaps(pixels, width, height, 4),
+		false => vec![pixels],
+	};
+
+	let (vk_format, levels) = match bake.format {
+		None => (Ktx2::VK_R8G8B8A8_UNORM, levels),
+		Some(format) => {
+			let levels = levels
+				.iter()
+				.enumerate()
+				.map(|(i, level)| encode(format, level, mip_size(width, i), mip_size(height, i)))
+				.collect::<Result<_, String>>()?;
+			(format.vk_format(), levels)
+		}
+	};
+
+	Ok(Ktx2 {
+		vk_format,
+		width,
+		height,
+		levels,
+	}
+	.to_bytes())
+}
+
+pub fn mip_size(size: u32, level: usize) -> u32 {
+	(size >> level).max(1)
+}
+
+// box filtered chain down to 1x1, level 0 first
+pub fn generate_mipmaps(data: Vec<u8>, width: u32, height: u32, channels: usize) -> Vec<Vec<u8>> {
+	let mut levels = vec![data];
+	let (mut width, mut height) = (width as usize, height as usize);
+
+	while width > 1 || height > 1 {
+		let (w, h) = ((width / 2).max(1), (height / 2).max(1));
+		let mut level = vec![0u8; w * h * channels];
+		{
+			let src = levels.last().unwrap();
+			for y in 0..h {
+				for x in 0..w {
+					let (x0, y0) = (x * 2, y * 2);
+					let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+					for c in 0..channels {
+						let sum = src[(y0 * width + x0) * channels + c] as u32
+							+ src[(y0 * width + x1) * channels + c] as u32
+							+ src[(y1 * width + x0) * channels + c] as u32
+							+ src[(y1 * width + x1) * channels + c] as u32;
+						level[(y * w + x) * channels + c] = ((sum + 2) / 4) as u8;
+					}
+				}
+			}
+		}
+		levels.push(level);
+		width = w;
+		height = h;
+	}
+
+	levels
+}
+
+// rgba - 8 bit RGBA pixels, edge blocks repeat the last row and column
+pub fn encode(format: CompressedFormat, rgba: &[u8], width: u32, height: u32) -> Result<Vec<u8>, String> {
+	if rgba.len() != (width * height * 4) as usize {
+		return Err(format!("wrong image size: {}x{}", width, height));
+	}
+
+	let encode_block: fn(&[[u8; 4]; 16], &mut Vec<u8>) = match format {
+		CompressedFormat::Bc1 => |block, out| out.extend_from_slice(&encode_bc1(block)),
+		CompressedFormat::Bc3 => |block, out| {
+			out.extend_from_slice(&encode_bc4(block, 3));
+			out.extend_from_slice(&encode_bc1(block));
+		},
+		CompressedFormat::Etc2Rgb => |block, out| out.extend_from_slice(&encode_etc1(block)),
+		CompressedFormat::Etc2Rgba => |block, out| {
+			out.extend_from_slice(&encode_eac(block, 3));
+			out.extend_from_slice(&encode_etc1(block));
+		},
+		_ => return Err(format!("{:?} encoding is not supported", format)),
+	};
+
+	let (width, height) = (width as usize, height as usize);
+	let mut out = Vec::with_capacity(format.level_size(width as u32, height as u32));
+
+	for by in 0..(height + 3) / 4 {
+		for bx in 0..(width + 3) / 4 {
+			let mut block = [[0u8; 4]; 16];
+			for y in 0..4 {
+				for x in 0..4 {
+					let px = (bx * 4 + x).min(width - 1);
+					let py = (by * 4 + y).min(height - 1);
+					let i = (py * width + px) * 4;
+					block[y * 4 + x].copy_from_slice(&rgba[i..i + 4]);
+				}
+			}
+			encode_block(&block, &mut out);
+		}
+	}
+
+	Ok(out)
+}
+
+fn color_distance(a: &[i32; 3], b: &[u8; 4]) -> i32 {
+	(0..3).map(|c| (a[c] - b[c] as i32) * (a[c] - b[c] as i32)).sum()
+}
+
+fn to_565(c: &[f32; 3]) -> u16 {
+	let r = (c[0].max(0.0).min(255.0) * 31.0 / 255.0).round() as u16;
+	let g = (c[1].max(0.0).min(255.0) * 63.0 / 255.0).round() as u16;
+	let b = (c[2].max(0.0).min(255.0) * 31.0 / 255.0).round() as u16;
+	(r << 11) | (g << 5) | b
+}
+
+fn from_565(c: u16) -> [i32; 3] {
+	let r = ((c >> 11) & 31) as i32;
+	let g = ((c >> 5) & 63) as i32;
+	let b = (c & 31) as i32;
+	[(r << 3) | (r >> 2), (g << 2) | (g >> 4), (b << 3) | (b >> 2)]
+}
+
+// endpoints are the extremes of the block along its principal axis
+fn encode_bc1(block: &[[u8; 4]; 16]) -> [u8; 8] {
+	let mut mean = [0f32; 3];
+	for p in block.iter() {
+		for c in 0..3 {
+			mean[c] += p[c] as f32 / 16.0;
+		}
+	}
+
+	let mut cov = [[0f32; 3]; 3];
+	for p in block.iter() {
+		let d = [p[0] as f32 - mean[0], p[1] as f32 - mean[1], p[2] as f32 - mean[2]];
+		for i in 0..3 {
+			for j in 0..3 {
+				cov[i][j] += d[i] * d[j];
+			}
+		}
+	}
+
+	let mut axis = [1f32, 1.0, 1.0];
+	for _ in 0..8 {
+		let next = [
+			cov[0][0] * axis[0] + cov[0][1] * axis[1] + cov[0][2] * axis[2],
+			cov[1][0] * axis[0] + cov[1][1] * axis[1] + cov[1][2] * axis[2],
+			cov[2][0] * axis[0] + cov[2][1] * axis[1] + cov[2][2] * axis[2],
+		];
+		let len = (next[0] * next[0] + next[1] * next[1] + next[2] * next[2]).sqrt();
+		if len < 1e-6 {
+			break;
+		}
+		axis = [next[0] / len, next[1] / len, next[2] / len];
+	}
+
+	let project = |p: &[u8; 4]| (0..3).map(|c| (p[c] as f32 - mean[c]) * axis[c]).sum::<f32>();
+	let (mut min, mut max) = (0f32, 0f32);
+	for p in block.iter() {
+		let t = project(p);
+		min = min.min(t);
+		max = max.max(t);
+	}
+
+	let endpoint = |t: f32| [mean[0] + axis[0] * t, mean[1] + axis[1] * t, mean[2] + axis[2] * t];
+	let (mut c0, mut c1) = (to_565(&endpoint(max)), to_565(&endpoint(min)));
+
+	// c0 > c1 selects the 4 color mode
+	if c0 < c1 {
+		std::mem::swap(&mut c0, &mut c1);
+	}
+
+	let mut indices = 0u32;
+	if c0 != c1 {
+		let (e0, e1) = (from_565(c0), from_565(c1));
+		let palette = [
+			e0,
+			e1,
+			[(2 * e0[0] + e1[0]) / 3, (2 * e0[1] + e1[1]) / 3, (2 * e0[2] + e1[2]) / 3],
+			[(e0[0] + 2 * e1[0]) / 3, (e0[1] + 2 * e1[1]) / 3, (e0[2] + 2 * e1[2]) / 3],
+		];
+
+		for (i, p) in block.iter().enumerate() {
+			let index = (0..4).min_by_key(|&k| color_distance(&palette[k], p)).unwrap();
+			indices |= (index as u32) << (i * 2);
+		}
+	}
+
+	let mut out = [0u8; 8];
+	out[0..2].copy_from_slice(&c0.to_le_bytes());
+	out[2..4].copy_from_slice(&c1.to_le_bytes());
+	out[4..8].copy_from_slice(&indices.to_le_bytes());
+	out
+}
+
+// 8 value mode of the BC3 alpha block
+fn encode_bc4(block: &[[u8; 4]; 16], channel: usize) -> [u8; 8] {
+	let a0 = block.iter().map(|p| p[channel]).max().unwrap() as i32;
+	let a1 = block.iter().map(|p| p[channel]).min().unwrap() as i32;
+
+	let mut palette = [a0, a1, 0, 0, 0, 0, 0, 0];
+	for i in 2..8 {
+		palette[i] = ((8 - i as i32) * a0 + (i as i32 - 1) * a1) / 7;
+	}
+
+	let mut indices = 0u64;
+	if a0 != a1 {
+		for (i, p) in block.iter().enumerate() {
+			let value = p[channel] as i32;
+			let index = (0..8).min_by_key(|&k| (palette[k] - value).abs()).unwrap();
+			indices |= (index as u64) << (i * 3);
+		}
+	}
+
+	let mut out = [0u8; 8];
+	out[0] = a0 as u8;
+	out[1] = a1 as u8;
+	out[2..8].copy_from_slice(&indices.to_le_bytes()[0..6]);
+	out
+}
+
+const ETC1_MODIFIERS: [[i32; 2]; 8] = [
+	[2, 8],
+	[5, 17],
+	[9, 29],
+	[13, 42],
+	[18, 60],
+	[24, 80],
+	[33, 106],
+	[47, 183],
+];
+
+// best table of a sub block, returns (error, table, pixel indices)
+fn etc1_sub_block(block: &[[u8; 4]; 16], pixels: &[(usize, usize)], base: &[i32; 3]) -> (i32, u32, Vec<u32>) {
+	let mut best = (i32::max_value(), 0, Vec::new());
+
+	for (table, modifiers) in ETC1_MODIFIERS.iter().enumerate() {
+		let values = [modifiers[0], modifiers[1], -modifiers[0], -modifiers[1]];
+		let mut error = 0;
+		let mut indices = Vec::with_capacity(pixels.len());
+
+		for &(x, y) in pixels.iter() {
+			let p = &block[y * 4 + x];
+			let (index, e) = (0..4)
+				.map(|k| {
+					let color = [
+						(base[0] + values[k]).max(0).min(255),
+						(base[1] + values[k]).max(0).min(255),
+						(base[2] + values[k]).max(0).min(255),
+					];
+					(k as u32, color_distance(&color, p))
+				})
+				.min_by_key(|(_, e)| *e)
+				.unwrap();
+			error += e;
+			indices.push(index);
+		}
+
+		if error < best.0 {
+			best = (error, table as u32, indices);
+		}
+	}
+
+	best
+}
+
+// ETC1 individual and differential modes, the result is a valid ETC2 block
+fn encode_etc1(block: &[[u8; 4]; 16]) -> [u8; 8] {
+	let mut best: Option<(i32, u64)> = None;
+
+	for &flip in [false, true].iter() {
+		let sub_blocks: Vec<Vec<(usize, usize)>> = (0..2)
+			.map(|s| {
+				let mut pixels = Vec::with_capacity(8);
+				for x in 0..4 {
+					for y in 0..4 {
+						let first = if flip { y < 2 } else { x < 2 };
+						if first == (s == 0) {
+							pixels.push((x, y));
+						}
+					}
+				}
+				pixels
+			})
+			.collect();
+
+		let averages: Vec<[f32; 3]> = sub_blocks
+			.iter()
+			.map(|pixels| {
+				let mut avg = [0f32; 3];
+				for &(x, y) in pixels.iter() {
+					for c in 0..3 {
+						avg[c] += block[y * 4 + x][c] as f32 / pixels.len() as f32;
+					}
+				}
+				avg
+			})
+			.collect();
+
+		for &differential in [true, false].iter() {
+			let (bits, max) = if differential { (5, 31.0) } else { (4, 15.0) };
+			let quantized: Vec<[i32; 3]> = averages
+				.iter()
+				.map(|avg| {
+					[
+						(avg[0] * max / 255.0).round() as i32,
+						(avg[1] * max / 255.0).round() as i32,
+						(avg[2] * max / 255.0).round() as i32,
+					]
+				})
+				.collect();
+
+			let mut q1 = quantized[1];
+			if differential {
+				// difference is stored in 3 bits
+				for c in 0..3 {
+					q1[c] = q1[c].max(quantized[0][c] - 4).min(quantized[0][c] + 3);
+				}
+			}
+
+			let expand = |q: &[i32; 3]| -> [i32; 3] {
+				match bits {
+					5 => [(q[0] << 3) | (q[0] >> 2), (q[1] << 3) | (q[1] >> 2), (q[2] << 3) | (q[2] >> 2)],
+					_ => [q[0] * 17, q[1] * 17, q[2] * 17],
+				}
+			};
+
+			let (e0, t0, i0) = etc1_sub_block(block, &sub_blocks[0], &expand(&quantized[0]));
+			let (e1, t1, i1) = etc1_sub_block(block, &sub_blocks[1], &expand(&q1));
+			let error = e0 + e1;
+
+			if best.as_ref().map_or(false, |(e, _)| *e <= error) {
+				continue;
+			}
+
+			let q0 = quantized[0];
+			let mut high: u32 = if differential {
+				let d = |c: usize| ((q1[c] - q0[c]) & 7) as u32;
+				(q0[0] as u32) << 27
+					| d(0) << 24 | (q0[1] as u32) << 19
+					| d(1) << 16 | (q0[2] as u32) << 11
+					| d(2) << 8 | 1 << 1
+			} else {
+				(q0[0] as u32) << 28
+					| (q1[0] as u32) << 24
+					| (q0[1] as u32) << 20
+					| (q1[1] as u32) << 16
+					| (q0[2] as u32) << 12
+					| (q1[2] as u32) << 8
+			};
+			high |= t0 << 5 | t1 << 2 | flip as u32;
+
+			// pixel index bits are ordered by columns
+			let mut low = 0u32;
+			for (pixels, indices) in [(&sub_blocks[0], &i0), (&sub_blocks[1], &i1)].iter() {
+				for (&(x, y), &index) in pixels.iter().zip(indices.iter()) {
+					let bit = x * 4 + y;
+					low |= (index >> 1) << (16 + bit) | (index & 1) << bit;
+				}
+			}
+
+			best = Some((error, (high as u64) << 32 | low as u64));
+		}
+	}
+
+	best.unwrap().1.to_be_bytes()
+}
+
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+	[-3, -6, -9, -15, 2, 5, 8, 14],
+	[-3, -7, -10, -13, 2, 6, 9, 12],
+	[-2, -5, -8, -13, 1, 4, 7, 12],
+	[-2, -4, -6, -13, 1, 3, 5, 12],
+	[-3, -6, -8, -12, 2, 5, 7, 11],
+	[-3, -7, -9, -11, 2, 6, 8, 10],
+	[-4, -7, -8, -11, 3, 6, 7, 10],
+	[-3, -5, -8, -11, 2, 4, 7, 10],
+	[-2, -6, -8, -10, 1, 5, 7, 9],
+	[-2, -5, -8, -10, 1, 4, 7, 9],
+	[-2, -4, -8, -10, 1, 3, 7, 9],
+	[-2, -5, -7, -10, 1, 4, 6, 9],
+	[-3, -4, -7, -10, 2, 3, 6, 9],
+	[-1, -2, -3, -10, 0, 1, 2, 9],
+	[-4, -6, -8, -9, 3, 5, 7, 8],
+	[-3, -5, -7, -9, 2, 4, 6, 8],
+];
+
+// ETC2 alpha block, exhaustive search over tables and multipliers
+fn encode_eac(block: &[[u8; 4]; 16], channel: usize) -> [u8; 8] {
+	let min = block.iter().map(|p| p[channel]).min().unwrap() as i32;
+	let max = block.iter().map(|p| p[channel]).max().unwrap() as i32;
+
+	let mut best = (i32::max_value(), 0u64);
+	for (table, modifiers) in EAC_MODIFIERS.iter().enumerate() {
+		for multiplier in 1..16 {
+			let low = modifiers[3] * multiplier;
+			let high = modifiers[7] * multiplier;
+			let base = ((min - low + max - high) / 2).max(0).min(255);
+
+			let mut error = 0;
+			let mut indices = 0u64;
+			for x in 0..4 {
+				for y in 0..4 {
+					let value = block[y * 4 + x][channel] as i32;
+					let (index, e) = (0..8)
+						.map(|k| {
+							let decoded = (base + modifiers[k] * multiplier).max(0).min(255);
+							(k as u64, (decoded - value) * (decoded - value))
+						})
+						.min_by_key(|(_, e)| *e)
+						.unwrap();
+					error += e;
+					indices |= index << (45 - 3 * (x * 4 + y));
+				}
+			}
+
+			if error < best.0 {
+				let bits = (base as u64) << 56 | (multiplier as u64) << 52 | (table as u64) << 48 | indices;
+				best = (error, bits);
+			}
+		}
+	}
+
+	best.1.to_be_bytes()
+}
@@ -10,13 +10,17 @@ use super::{
 	Children,
 	DirectionalLight,
     SharedFrameBuffer,
-    SharedRenderBuffer
+    SharedRenderBuffer,
+	Lod,
+	Instances,
 };
 
 pub fn create_world() -> World {
 	let mut world = World::new();
 	world.register::<SharedGeometry>();
 	world.register::<SharedMaterials>();
+	world.register::<Lod>();
+	world.register::<Instances>();
 	world.register::<Transform>();
 	world.register::<PerspectiveCamera>();
 	world.register::<SharedFrameBuffer>();
@@ -1,5 +1,6 @@
-use core::{BufferGeometry, BufferData, BufferType};
-use math::{Vector2, Vector3, Vector};
+use core::{BBox3, BufferGeometry, BufferData, BufferGroup, BufferType, DrawMode};
+use math::{Matrix4, Vector2, Vector3, Vector};
+use std::collections::HashMap;
 use std::f32::consts::PI;
 
 #[allow(dead_code)]
@@ -315,3 +316,901 @@ pub fn simple_plane() -> BufferGeometry {
 	geom.set_indices(indices);
 	geom
 }
+
+
+fn build_geometry(vertices: Vec<Vector3<f32>>, normals: Vec<Vector3<f32>>, uvs: Vec<Vector2<f32>>, indices: Vec<u32>) -> BufferGeometry {
+	let mut geom = BufferGeometry::new();
+	geom.create_buffer_attribute(BufferType::Position, BufferData::Vector3(vertices));
+	geom.create_buffer_attribute(BufferType::Normal, BufferData::Vector3(normals));
+	geom.create_buffer_attribute(BufferType::UV(0), BufferData::Vector2(uvs));
+	geom.set_indices(indices);
+	geom
+}
+
+
+#[allow(dead_code)]
+pub fn param_cylinder(radius_top: f32, radius_bottom: f32, height: f32, radial_segments: usize, height_segments: usize, open_ended: bool, theta_start: f32, theta_length: f32) -> BufferGeometry {
+
+	let half_height = height / 2.0;
+	let slope = (radius_bottom - radius_top) / height;
+
+	// buffers
+	let mut indices = Vec::new();
+	let mut vertices = Vec::new();
+	let mut normals = Vec::new();
+	let mut uvs = Vec::new();
+
+	// torso
+	for y in 0..=height_segments {
+		let v = y as f32 / height_segments as f32;
+		let radius = v * (radius_bottom - radius_top) + radius_top;
+
+		for x in 0..=radial_segments {
+			let u = x as f32 / radial_segments as f32;
+			let theta = u * theta_length + theta_start;
+			let (sin, cos) = theta.sin_cos();
+
+			vertices.push(Vector3::new(radius * sin, - v * height + half_height, radius * cos));
+			let mut normal = Vector3::new(sin, slope, cos);
+			normal.normalize();
+			normals.push(normal);
+			uvs.push(Vector2::new(u, 1.0 - v));
+		}
+	}
+
+	let row = radial_segments + 1;
+	for y in 0..height_segments {
+		for x in 0..radial_segments {
+			let a = (row * y + x) as u32;
+			let b = (row * (y + 1) + x) as u32;
+			let c = (row * (y + 1) + x + 1) as u32;
+			let d = (row * y + x + 1) as u32;
+
+			// faces
+			indices.push( a );
+			indices.push( b );
+			indices.push( d );
+
+			indices.push( b );
+			indices.push( c );
+			indices.push( d );
+		}
+	}
+
+	// caps
+	let caps = [(true, radius_top), (false, radius_bottom)];
+	for (top, radius) in caps.iter().filter(|(_, radius)| !open_ended && *radius > 0.0) {
+		let sign = if *top { 1.0 } else { -1.0 };
+
+		// one center vertex per segment for uvs
+		let center_start = vertices.len();
+		for _ in 0..radial_segments {
+			vertices.push(Vector3::new(0.0, half_height * sign, 0.0));
+			normals.push(Vector3::new(0.0, sign, 0.0));
+			uvs.push(Vector2::new(0.5, 0.5));
+		}
+
+		let ring_start = vertices.len();
+		for x in 0..=radial_segments {
+			let u = x as f32 / radial_segments as f32;
+			let theta = u * theta_length + theta_start;
+			let (sin, cos) = theta.sin_cos();
+
+			vertices.push(Vector3::new(radius * sin, half_height * sign, radius * cos));
+			normals.push(Vector3::new(0.0, sign, 0.0));
+			uvs.push(Vector2::new(cos * 0.5 + 0.5, sin * 0.5 * sign + 0.5));
+		}
+
+		for x in 0..radial_segments {
+			let c = (center_start + x) as u32;
+			let i = (ring_start + x) as u32;
+			if *top {
+				indices.extend_from_slice(&[i, i + 1, c]);
+			} else {
+				indices.extend_from_slice(&[i + 1, i, c]);
+			}
+		}
+	}
+
+	build_geometry(vertices, normals, uvs, indices)
+}
+
+
+#[allow(dead_code)]
+pub fn cylinder(radius: f32, height: f32, radial_segments: usize) -> BufferGeometry {
+	param_cylinder(radius, radius, height, radial_segments, 1, false, 0.0, PI * 2.0)
+}
+
+
+#[allow(dead_code)]
+pub fn cone(radius: f32, height: f32, radial_segments: usize) -> BufferGeometry {
+	param_cylinder(0.0, radius, height, radial_segments, 1, false, 0.0, PI * 2.0)
+}
+
+
+#[allow(dead_code)]
+pub fn param_torus(radius: f32, tube: f32, radial_segments: usize, tubular_segments: usize, arc: f32) -> BufferGeometry {
+
+	// buffers
+	let mut indices = Vec::new();
+	let mut vertices = Vec::new();
+	let mut normals = Vec::new();
+	let mut uvs = Vec::new();
+
+	for j in 0..=radial_segments {
+		for i in 0..=tubular_segments {
+			let u = i as f32 / tubular_segments as f32 * arc;
+			let v = j as f32 / radial_segments as f32 * PI * 2.0;
+
+			let vertex = Vector3::new(
+				(radius + tube * v.cos()) * u.cos(),
+				(radius + tube * v.cos()) * u.sin(),
+				tube * v.sin(),
+			);
+
+			// normal points away from the tube center
+			let mut normal = vertex.clone();
+			normal.sub(&Vector3::new(radius * u.cos(), radius * u.sin(), 0.0)).normalize();
+
+			vertices.push(vertex);
+			normals.push(normal);
+			uvs.push(Vector2::new(i as f32 / tubular_segments as f32, j as f32 / radial_segments as f32));
+		}
+	}
+
+	let row = tubular_segments + 1;
+	for j in 1..=radial_segments {
+		for i in 1..=tubular_segments {
+			let a = (row * j + i - 1) as u32;
+			let b = (row * (j - 1) + i - 1) as u32;
+			let c = (row * (j - 1) + i) as u32;
+			let d = (row * j + i) as u32;
+
+			indices.extend_from_slice(&[a, b, d]);
+			indices.extend_from_slice(&[b, c, d]);
+		}
+	}
+
+	build_geometry(vertices, normals, uvs, indices)
+}
+
+
+#[allow(dead_code)]
+pub fn torus(radius: f32, tube: f32, radial_segments: usize, tubular_segments: usize) -> BufferGeometry {
+	param_torus(radius, tube, radial_segments, tubular_segments, PI * 2.0)
+}
+
+
+#[allow(dead_code)]
+pub fn param_torus_knot(radius: f32, tube: f32, tubular_segments: usize, radial_segments: usize, p: f32, q: f32) -> BufferGeometry {
+
+	// point on the knot curve
+	let curve = |u: f32| {
+		let (su, cu) = u.sin_cos();
+		let qu_over_p = q / p * u;
+		let cs = qu_over_p.cos();
+		Vector3::new(
+			radius * (2.0 + cs) * 0.5 * cu,
+			radius * (2.0 + cs) * su * 0.5,
+			radius * qu_over_p.sin() * 0.5,
+		)
+	};
+
+	// buffers
+	let mut indices = Vec::new();
+	let mut vertices = Vec::new();
+	let mut normals = Vec::new();
+	let mut uvs = Vec::new();
+
+	for i in 0..=tubular_segments {
+		let u = i as f32 / tubular_segments as f32 * p * PI * 2.0;
+
+		// frame from the curve and a point slightly ahead
+		let p1 = curve(u);
+		let p2 = curve(u + 0.01);
+
+		let mut t = Vector3::new_zero();
+		t.sub_vectors(&p2, &p1);
+		let mut n = Vector3::new_zero();
+		n.add_vectors(&p2, &p1);
+		let mut b = Vector3::new_zero();
+		b.cross_vectors(&t, &n);
+		n.cross_vectors(&b, &t);
+		b.normalize();
+		n.normalize();
+
+		for j in 0..=radial_segments {
+			let v = j as f32 / radial_segments as f32 * PI * 2.0;
+			let cx = - tube * v.cos();
+			let cy = tube * v.sin();
+
+			let vertex = Vector3::new(
+				p1.x + (cx * n.x + cy * b.x),
+				p1.y + (cx * n.y + cy * b.y),
+				p1.z + (cx * n.z + cy * b.z),
+			);
+
+			let mut normal = Vector3::new_zero();
+			normal.sub_vectors(&vertex, &p1).normalize();
+
+			vertices.push(vertex);
+			normals.push(normal);
+			uvs.push(Vector2::new(i as f32 / tubular_segments as f32, j as f32 / radial_segments as f32));
+		}
+	}
+
+	let row = radial_segments + 1;
+	for j in 1..=tubular_segments {
+		for i in 1..=radial_segments {
+			let a = (row * (j - 1) + i - 1) as u32;
+			let b = (row * j + i - 1) as u32;
+			let c = (row * j + i) as u32;
+			let d = (row * (j - 1) + i) as u32;
+
+			indices.extend_from_slice(&[a, b, d]);
+			indices.extend_from_slice(&[b, c, d]);
+		}
+	}
+
+	build_geometry(vertices, normals, uvs, indices)
+}
+
+
+#[allow(dead_code)]
+pub fn torus_knot(radius: f32, tube: f32) -> BufferGeometry {
+	param_torus_knot(radius, tube, 64, 8, 2.0, 3.0)
+}
+
+
+#[allow(dead_code)]
+pub fn param_circle(radius: f32, segments: usize, theta_start: f32, theta_length: f32) -> BufferGeometry {
+
+	// center
+	let mut vertices = vec![Vector3::new_zero()];
+	let mut normals = vec![Vector3::new(0.0, 0.0, 1.0)];
+	let mut uvs = vec![Vector2::new(0.5, 0.5)];
+	let mut indices = Vec::new();
+
+	for s in 0..=segments {
+		let segment = theta_start + s as f32 / segments as f32 * theta_length;
+		let (sin, cos) = segment.sin_cos();
+
+		vertices.push(Vector3::new(radius * cos, radius * sin, 0.0));
+		normals.push(Vector3::new(0.0, 0.0, 1.0));
+		uvs.push(Vector2::new((cos + 1.0) / 2.0, (sin + 1.0) / 2.0));
+	}
+
+	for i in 1..=segments as u32 {
+		indices.extend_from_slice(&[i, i + 1, 0]);
+	}
+
+	build_geometry(vertices, normals, uvs, indices)
+}
+
+
+#[allow(dead_code)]
+pub fn circle(radius: f32, segments: usize) -> BufferGeometry {
+	param_circle(radius, segments, 0.0, PI * 2.0)
+}
+
+
+#[allow(dead_code)]
+pub fn param_ring(inner_radius: f32, outer_radius: f32, theta_segments: usize, phi_segments: usize, theta_start: f32, theta_length: f32) -> BufferGeometry {
+
+	// buffers
+	let mut indices = Vec::new();
+	let mut vertices = Vec::new();
+	let mut normals = Vec::new();
+	let mut uvs = Vec::new();
+
+	let radius_step = (outer_radius - inner_radius) / phi_segments as f32;
+
+	for j in 0..=phi_segments {
+		let radius = inner_radius + radius_step * j as f32;
+
+		for i in 0..=theta_segments {
+			let segment = theta_start + i as f32 / theta_segments as f32 * theta_length;
+			let (sin, cos) = segment.sin_cos();
+			let (x, y) = (radius * cos, radius * sin);
+
+			vertices.push(Vector3::new(x, y, 0.0));
+			normals.push(Vector3::new(0.0, 0.0, 1.0));
+			uvs.push(Vector2::new((x / outer_radius + 1.0) / 2.0, (y / outer_radius + 1.0) / 2.0));
+		}
+	}
+
+	let row = theta_segments + 1;
+	for j in 0..phi_segments {
+		for i in 0..theta_segments {
+			let segment = i + j * row;
+			let a = segment as u32;
+			let b = (segment + row) as u32;
+			let c = (segment + row + 1) as u32;
+			let d = (segment + 1) as u32;
+
+			indices.extend_from_slice(&[a, b, d]);
+			indices.extend_from_slice(&[b, c, d]);
+		}
+	}
+
+	build_geometry(vertices, normals, uvs, indices)
+}
+
+
+#[allow(dead_code)]
+pub fn ring(inner_radius: f32, outer_radius: f32, segments: usize) -> BufferGeometry {
+	param_ring(inner_radius, outer_radius, segments, 1, 0.0, PI * 2.0)
+}
+
+
+// rotates the profile `points` (x is the radius) around the y axis
+#[allow(dead_code)]
+pub fn param_lathe(points: &[Vector2<f32>], segments: usize, phi_start: f32, phi_length: f32) -> BufferGeometry {
+
+	// buffers
+	let mut indices = Vec::new();
+	let mut vertices = Vec::new();
+	let mut normals = Vec::new();
+	let mut uvs = Vec::new();
+
+	if points.len() < 2 {
+		return build_geometry(vertices, normals, uvs, indices);
+	}
+
+	// profile normals, averaged between neighbouring segments
+	let last = points.len() - 1;
+	let mut profile_normals = Vec::with_capacity(points.len());
+	let mut prev_normal = Vector2::new(0.0, 0.0);
+	for j in 0..=last {
+		if j == last {
+			profile_normals.push(prev_normal.clone());
+			continue;
+		}
+
+		let dx = points[j + 1].x - points[j].x;
+		let dy = points[j + 1].y - points[j].y;
+		let mut normal = Vector2::new(dy, -dx);
+		let current = normal.clone();
+		if j > 0 {
+			normal.add(&prev_normal);
+		}
+		normal.normalize();
+		profile_normals.push(normal.clone());
+		prev_normal = current;
+	}
+	if let Some(normal) = profile_normals.last_mut() {
+		normal.normalize();
+	}
+
+	for i in 0..=segments {
+		let phi = phi_start + i as f32 / segments as f32 * phi_length;
+		let (sin, cos) = phi.sin_cos();
+
+		for j in 0..=last {
+			let point = &points[j];
+			let profile_normal = &profile_normals[j];
+
+			vertices.push(Vector3::new(point.x * sin, point.y, point.x * cos));
+			let mut normal = Vector3::new(profile_normal.x * sin, profile_normal.y, profile_normal.x * cos);
+			normal.normalize();
+			normals.push(normal);
+			uvs.push(Vector2::new(i as f32 / segments as f32, j as f32 / last as f32));
+		}
+	}
+
+	let row = points.len();
+	for i in 0..segments {
+		for j in 0..last {
+			let base = j + i * row;
+			let a = base as u32;
+			let b = (base + row) as u32;
+			let c = (base + row + 1) as u32;
+			let d = (base + 1) as u32;
+
+			indices.extend_from_slice(&[a, b, d]);
+			indices.extend_from_slice(&[c, d, b]);
+		}
+	}
+
+	build_geometry(vertices, normals, uvs, indices)
+}
+
+
+#[allow(dead_code)]
+pub fn lathe(points: &[Vector2<f32>], segments: usize) -> BufferGeometry {
+	param_lathe(points, segments, 0.0, PI * 2.0)
+}
+
+
+// cylinder of `length` with hemispheres of `radius` at both ends
+#[allow(dead_code)]
+pub fn param_capsule(radius: f32, length: f32, cap_segments: usize, radial_segments: usize) -> BufferGeometry {
+	let half_length = length / 2.0;
+	let mut points = Vec::with_capacity(cap_segments * 2 + 2);
+
+	for i in 0..=cap_segments {
+		let angle = -PI / 2.0 + i as f32 / cap_segments as f32 * PI / 2.0;
+		points.push(Vector2::new(radius * angle.cos(), radius * angle.sin() - half_length));
+	}
+	for i in 0..=cap_segments {
+		let angle = i as f32 / cap_segments as f32 * PI / 2.0;
+		points.push(Vector2::new(radius * angle.cos(), radius * angle.sin() + half_length));
+	}
+
+	// exact poles
+	points[0].x = 0.0;
+	let last = points.len() - 1;
+	points[last].x = 0.0;
+
+	param_lathe(&points, radial_segments, 0.0, PI * 2.0)
+}
+
+
+#[allow(dead_code)]
+pub fn capsule(radius: f32, length: f32) -> BufferGeometry {
+	param_capsule(radius, length, 8, 16)
+}
+
+
+// subdivided icosahedron, every subdivision splits each face in four
+#[allow(dead_code)]
+pub fn param_icosphere(radius: f32, subdivisions: usize) -> BufferGeometry {
+	let t = (1.0 + 5.0f32.sqrt()) / 2.0;
+
+	let mut points: Vec<Vector3<f32>> = [
+		(-1.0, t, 0.0), (1.0, t, 0.0), (-1.0, -t, 0.0), (1.0, -t, 0.0),
+		(0.0, -1.0, t), (0.0, 1.0, t), (0.0, -1.0, -t), (0.0, 1.0, -t),
+		(t, 0.0, -1.0), (t, 0.0, 1.0), (-t, 0.0, -1.0), (-t, 0.0, 1.0),
+	]
+		.iter()
+		.map(|(x, y, z)| {
+			let mut point = Vector3::new(*x, *y, *z);
+			point.normalize();
+			point
+		})
+		.collect();
+
+	let mut faces: Vec<[usize; 3]> = vec![
+		[0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+		[1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+		[3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+		[4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+	];
+
+	for _ in 0..subdivisions {
+		let mut midpoints = HashMap::new();
+		let mut midpoint = |a: usize, b: usize, points: &mut Vec<Vector3<f32>>| {
+			*midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+				let mut point = Vector3::new_zero();
+				point.add_vectors(&points[a], &points[b]).normalize();
+				points.push(point);
+				points.len() - 1
+			})
+		};
+
+		faces = faces
+			.iter()
+			.flat_map(|[a, b, c]| {
+				let ab = midpoint(*a, *b, &mut points);
+				let bc = midpoint(*b, *c, &mut points);
+				let ca = midpoint(*c, *a, &mut points);
+				vec![[*a, ab, ca], [*b, bc, ab], [*c, ca, bc], [ab, bc, ca]]
+			})
+			.collect();
+	}
+
+	let azimuth = |p: &Vector3<f32>| p.z.atan2(-p.x);
+	let inclination = |p: &Vector3<f32>| (-p.y).atan2((p.x * p.x + p.z * p.z).sqrt());
+
+	// buffers
+	let mut vertices = Vec::with_capacity(faces.len() * 3);
+	let mut normals = Vec::with_capacity(faces.len() * 3);
+	let mut uvs = Vec::with_capacity(faces.len() * 3);
+
+	for face in faces.iter() {
+		let corners: Vec<&Vector3<f32>> = face.iter().map(|i| &points[*i]).collect();
+		let mut face_uvs: Vec<Vector2<f32>> = corners
+			.iter()
+			.map(|p| Vector2::new(azimuth(p) / 2.0 / PI + 0.5, 1.0 - (inclination(p) / PI + 0.5)))
+			.collect();
+
+		// faces across the seam wrap around
+		let max = face_uvs.iter().fold(0.0f32, |a, uv| a.max(uv.x));
+		let min = face_uvs.iter().fold(1.0f32, |a, uv| a.min(uv.x));
+		if max > 0.9 && min < 0.1 {
+			face_uvs.iter_mut().filter(|uv| uv.x < 0.2).for_each(|uv| uv.x += 1.0);
+		}
+
+		// poles take the azimuth of the face center
+		let mut center = Vector3::new_zero();
+		corners.iter().for_each(|p| {
+			center.add(p);
+		});
+		for (p, uv) in corners.iter().zip(face_uvs.iter_mut()) {
+			if p.x.abs() < 1e-6 && p.z.abs() < 1e-6 {
+				uv.x = azimuth(&center) / 2.0 / PI + 0.5;
+			}
+		}
+
+		for (p, uv) in corners.iter().zip(face_uvs.drain(..)) {
+			let mut vertex = (*p).clone();
+			vertex.multiply_scalar(radius);
+			vertices.push(vertex);
+			normals.push((*p).clone());
+			uvs.push(uv);
+		}
+	}
+
+	let indices = (0..vertices.len() as u32).collect();
+	let mut geom = build_geometry(vertices, normals, uvs, indices);
+	geom.merge_vertices(0.0);
+	geom
+}
+
+
+#[allow(dead_code)]
+pub fn icosphere(radius: f32) -> BufferGeometry {
+	param_icosphere(radius, 3)
+}
+
+
+// tube around `path`, a curve over 0..1
+#[allow(dead_code)]
+pub fn param_tube<F>(path: F, tubular_segments: usize, radius: f32, radial_segments: usize, closed: bool) -> BufferGeometry
+where
+	F: Fn(f32) -> Vector3<f32>,
+{
+	let delta = 0.0001;
+	let tangent = |u: f32| {
+		let mut t = Vector3::new_zero();
+		t.sub_vectors(&path((u + delta).min(1.0)), &path((u - delta).max(0.0))).normalize();
+		t
+	};
+
+	// parallel transport frames
+	let tangents: Vec<Vector3<f32>> = (0..=tubular_segments).map(|i| tangent(i as f32 / tubular_segments as f32)).collect();
+	let mut frame_normals = Vec::with_capacity(tangents.len());
+	let mut binormals = Vec::with_capacity(tangents.len());
+
+	let t0 = &tangents[0];
+	let mut axis = Vector3::new(1.0, 0.0, 0.0);
+	if t0.y.abs() <= t0.x.abs() && t0.y.abs() <= t0.z.abs() {
+		axis.set(0.0, 1.0, 0.0);
+	} else if t0.z.abs() <= t0.x.abs() && t0.z.abs() <= t0.y.abs() {
+		axis.set(0.0, 0.0, 1.0);
+	}
+	let mut side = Vector3::new_zero();
+	side.cross_vectors(t0, &axis).normalize();
+	let mut normal = Vector3::new_zero();
+	normal.cross_vectors(t0, &side);
+	let mut binormal = Vector3::new_zero();
+	binormal.cross_vectors(t0, &normal);
+	frame_normals.push(normal);
+	binormals.push(binormal);
+
+	for i in 1..tangents.len() {
+		let mut normal = frame_normals[i - 1].clone();
+		let mut rotation_axis = Vector3::new_zero();
+		rotation_axis.cross_vectors(&tangents[i - 1], &tangents[i]);
+		if rotation_axis.length() > std::f32::EPSILON {
+			rotation_axis.normalize();
+			let theta = tangents[i - 1].clone().dot(&tangents[i]).max(-1.0).min(1.0).acos();
+			let mut rotation = Matrix4::new();
+			rotation.makerotation_axis(&rotation_axis, theta);
+			normal.apply_matrix_4(&rotation);
+		}
+		let mut binormal = Vector3::new_zero();
+		binormal.cross_vectors(&tangents[i], &normal);
+		frame_normals.push(normal);
+		binormals.push(binormal);
+	}
+
+	// spread the twist between the ends of closed tubes
+	if closed {
+		let last = tangents.len() - 1;
+		let mut theta = frame_normals[0].clone().dot(&frame_normals[last]).max(-1.0).min(1.0).acos() / last as f32;
+		let mut twist = Vector3::new_zero();
+		twist.cross_vectors(&frame_normals[0], &frame_normals[last]);
+		if tangents[0].clone().dot(&twist) > 0.0 {
+			theta = -theta;
+		}
+		for i in 1..=last {
+			let mut rotation = Matrix4::new();
+			rotation.makerotation_axis(&tangents[i], theta * i as f32);
+			frame_normals[i].apply_matrix_4(&rotation);
+			binormals[i].cross_vectors(&tangents[i], &frame_normals[i]);
+		}
+	}
+
+	// buffers
+	let mut indices = Vec::new();
+	let mut vertices = Vec::new();
+	let mut normals = Vec::new();
+	let mut uvs = Vec::new();
+
+	for i in 0..=tubular_segments {
+		// closed tubes end on the first ring
+		let frame = if closed && i == tubular_segments { 0 } else { i };
+		let point = path(frame as f32 / tubular_segments as f32);
+		let (n, b) = (&frame_normals[frame], &binormals[frame]);
+
+		for j in 0..=radial_segments {
+			let v = j as f32 / radial_segments as f32 * PI * 2.0;
+			let (sin, cos) = (v.sin(), -v.cos());
+
+			let mut normal = Vector3::new(cos * n.x + sin * b.x, cos * n.y + sin * b.y, cos * n.z + sin * b.z);
+			normal.normalize();
+			let mut vertex = normal.clone();
+			vertex.multiply_scalar(radius).add(&point);
+
+			vertices.push(vertex);
+			normals.push(normal);
+			uvs.push(Vector2::new(i as f32 / tubular_segments as f32, j as f32 / radial_segments as f32));
+		}
+	}
+
+	let row = radial_segments + 1;
+	for j in 1..=tubular_segments {
+		for i in 1..=radial_segments {
+			let a = (row * (j - 1) + i - 1) as u32;
+			let b = (row * j + i - 1) as u32;
+			let c = (row * j + i) as u32;
+			let d = (row * (j - 1) + i) as u32;
+
+			indices.extend_from_slice(&[a, b, d]);
+			indices.extend_from_slice(&[b, c, d]);
+		}
+	}
+
+	build_geometry(vertices, normals, uvs, indices)
+}
+
+
+#[allow(dead_code)]
+pub fn tube<F>(path: F, radius: f32) -> BufferGeometry
+where
+	F: Fn(f32) -> Vector3<f32>,
+{
+	param_tube(path, 64, radius, 8, false)
+}
+
+
+// extrudes the polygon `shape` along z from 0 to `depth`, sides are split into `steps`
+#[allow(dead_code)]
+pub fn param_extrude(shape: &[Vector2<f32>], depth: f32, steps: usize) -> BufferGeometry {
+	let mut shape = shape.to_vec();
+	if shape.len() > 1 && shape[0] == shape[shape.len() - 1] {
+		shape.pop();
+	}
+
+	// counter clockwise winding
+	let area: f32 = (0..shape.len())
+		.map(|i| {
+			let (a, b) = (&shape[i], &shape[(i + 1) % shape.len()]);
+			a.x * b.y - b.x * a.y
+		})
+		.sum();
+	if area < 0.0 {
+		shape.reverse();
+	}
+
+	// buffers
+	let mut indices = Vec::new();
+	let mut vertices = Vec::new();
+	let mut normals = Vec::new();
+	let mut uvs = Vec::new();
+
+	if shape.len() < 3 {
+		return build_geometry(vertices, normals, uvs, indices);
+	}
+
+	// caps
+	let triangles = triangulate(&shape);
+	for (z, sign) in [(depth, 1.0), (0.0, -1.0)].iter() {
+		let start = vertices.len() as u32;
+		for point in shape.iter() {
+			vertices.push(Vector3::new(point.x, point.y, *z));
+			normals.push(Vector3::new(0.0, 0.0, *sign));
+			uvs.push(point.clone());
+		}
+		for tri in triangles.chunks(3) {
+			if *sign > 0.0 {
+				indices.extend_from_slice(&[start + tri[0], start + tri[1], start + tri[2]]);
+			} else {
+				indices.extend_from_slice(&[start + tri[0], start + tri[2], start + tri[1]]);
+			}
+		}
+	}
+
+	// sides, flat shaded per edge
+	let perimeter: f32 = (0..shape.len())
+		.map(|i| {
+			let mut edge = shape[(i + 1) % shape.len()].clone();
+			edge.sub(&shape[i]);
+			edge.length()
+		})
+		.sum();
+	let mut distance = 0.0;
+
+	for i in 0..shape.len() {
+		let (a, b) = (&shape[i], &shape[(i + 1) % shape.len()]);
+		let mut edge = b.clone();
+		edge.sub(a);
+		let length = edge.length();
+		let mut normal = Vector3::new(edge.y, -edge.x, 0.0);
+		normal.normalize();
+
+		let start = vertices.len() as u32;
+		for s in 0..=steps {
+			let v = s as f32 / steps as f32;
+			let z = depth * v;
+			vertices.push(Vector3::new(a.x, a.y, z));
+			vertices.push(Vector3::new(b.x, b.y, z));
+			normals.push(normal.clone());
+			normals.push(normal.clone());
+			uvs.push(Vector2::new(distance / perimeter, v));
+			uvs.push(Vector2::new((distance + length) / perimeter, v));
+		}
+		for s in 0..steps as u32 {
+			let a = start + s * 2;
+			indices.extend_from_slice(&[a, a + 1, a + 3]);
+			indices.extend_from_slice(&[a, a + 3, a + 2]);
+		}
+
+		distance += length;
+	}
+
+	build_geometry(vertices, normals, uvs, indices)
+}
+
+
+#[allow(dead_code)]
+pub fn extrude(shape: &[Vector2<f32>], depth: f32) -> BufferGeometry {
+	param_extrude(shape, depth, 1)
+}
+
+
+fn build_lines(vertices: Vec<Vector3<f32>>, indices: Vec<u32>, draw_mode: DrawMode) -> BufferGeometry {
+	let mut geom = BufferGeometry::new();
+	geom.create_buffer_attribute(BufferType::Position, BufferData::Vector3(vertices));
+	geom.set_indices(indices);
+	geom.draw_mode = draw_mode;
+	geom
+}
+
+
+// pairs of points, a trailing odd point is ignored
+#[allow(dead_code)]
+pub fn line_segments(points: &[Vector3<f32>]) -> BufferGeometry {
+	let count = points.len() / 2 * 2;
+	build_lines(points[..count].to_vec(), (0..count as u32).collect(), DrawMode::Lines)
+}
+
+
+#[allow(dead_code)]
+pub fn line_strip(points: &[Vector3<f32>], closed: bool) -> BufferGeometry {
+	let draw_mode = if closed { DrawMode::LineLoop } else { DrawMode::LineStrip };
+	build_lines(points.to_vec(), (0..points.len() as u32).collect(), draw_mode)
+}
+
+
+// lines on the xz plane centered at the origin
+#[allow(dead_code)]
+pub fn param_grid(width: f32, depth: f32, width_divisions: usize, depth_divisions: usize) -> BufferGeometry {
+	let width_half = width / 2.0;
+	let depth_half = depth / 2.0;
+
+	let mut vertices = Vec::with_capacity((width_divisions + depth_divisions + 2) * 2);
+
+	for i in 0..=width_divisions {
+		let x = i as f32 / width_divisions as f32 * width - width_half;
+		vertices.push(Vector3::new(x, 0.0, -depth_half));
+		vertices.push(Vector3::new(x, 0.0, depth_half));
+	}
+
+	for i in 0..=depth_divisions {
+		let z = i as f32 / depth_divisions as f32 * depth - depth_half;
+		vertices.push(Vector3::new(-width_half, 0.0, z));
+		vertices.push(Vector3::new(width_half, 0.0, z));
+	}
+
+	let indices = (0..vertices.len() as u32).collect();
+	build_lines(vertices, indices, DrawMode::Lines)
+}
+
+
+#[allow(dead_code)]
+pub fn grid(size: f32, divisions: usize) -> BufferGeometry {
+	param_grid(size, size, divisions, divisions)
+}
+
+
+// one group per axis with material_index 0, 1, 2 for x, y, z
+#[allow(dead_code)]
+pub fn axes(size: f32) -> BufferGeometry {
+	let vertices = vec![
+		Vector3::new(0.0, 0.0, 0.0), Vector3::new(size, 0.0, 0.0),
+		Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, size, 0.0),
+		Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, size),
+	];
+
+	let mut geom = build_lines(vertices, (0..6).collect(), DrawMode::Lines);
+	geom.groups = ["x", "y", "z"]
+		.iter()
+		.enumerate()
+		.map(|(i, name)| BufferGroup {
+			start: i * 2,
+			count: 2,
+			material_index: i,
+			name: Some(name.to_string()),
+		})
+		.collect();
+	geom
+}
+
+
+// the 12 edges of a box, e.g. from `BufferGeometry::get_b_box`
+#[allow(dead_code)]
+pub fn box_outline(b_box: &BBox3<f32>) -> BufferGeometry {
+	let min = b_box.get_min();
+	let max = b_box.get_max();
+
+	let vertices = vec![
+		Vector3::new(min.x, min.y, min.z),
+		Vector3::new(max.x, min.y, min.z),
+		Vector3::new(max.x, max.y, min.z),
+		Vector3::new(min.x, max.y, min.z),
+		Vector3::new(min.x, min.y, max.z),
+		Vector3::new(max.x, min.y, max.z),
+		Vector3::new(max.x, max.y, max.z),
+		Vector3::new(min.x, max.y, max.z),
+	];
+
+	let indices = vec![
+		0, 1, 1, 2, 2, 3, 3, 0,
+		4, 5, 5, 6, 6, 7, 7, 4,
+		0, 4, 1, 5, 2, 6, 3, 7,
+	];
+
+	build_lines(vertices, indices, DrawMode::Lines)
+}
+
+
+// ear clipping of a counter clockwise simple polygon
+fn triangulate(points: &[Vector2<f32>]) -> Vec<u32> {
+	let cross = |a: &Vector2<f32>, b: &Vector2<f32>, c: &Vector2<f32>| (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x);
+
+	let mut remaining: Vec<usize> = (0..points.len()).collect();
+	let mut indices = Vec::with_capacity((points.len() - 2) * 3);
+
+	while remaining.len() > 3 {
+		let count = remaining.len();
+		let ear = (0..count).find(|i| {
+			let (a, b, c) = (remaining[(i + count - 1) % count], remaining[*i], remaining[(i + 1) % count]);
+			let (pa, pb, pc) = (&points[a], &points[b], &points[c]);
+			if cross(pa, pb, pc) <= 0.0 {
+				return false;
+			}
+			!remaining.iter().filter(|e| **e != a && **e != b && **e != c).any(|e| {
+				let p = &points[*e];
+				cross(pa, pb, p) >= 0.0 && cross(pb, pc, p) >= 0.0 && cross(pc, pa, p) >= 0.0
+			})
+		});
+
+		match ear {
+			Some(i) => {
+				indices.push(remaining[(i + count - 1) % count] as u32);
+				indices.push(remaining[i] as u32);
+				indices.push(remaining[(i + 1) % count] as u32);
+				remaining.remove(i);
+			}
+			// degenerate polygon
+			None => break,
+		}
+	}
+
+	if remaining.len() == 3 {
+		indices.extend(remaining.iter().map(|e| *e as u32));
+	}
+	indices
+}
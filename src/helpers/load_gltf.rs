@@ -6,7 +6,7 @@ extern crate uuid;
 
 use std::string::ToString;
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::boxed::Box;
 use std::error::Error as StdError;
 use math::{
@@ -68,7 +68,6 @@ use core::{
 };
 
 struct Context {
-	path: PathBuf,
 	doc: Document,
 	images: Vec<TextureData>,
 	textures: Vec<SharedTexture2D>,
@@ -78,10 +77,41 @@ struct Context {
 }
 
 
+// imported gltf document with its buffers and images, can be instantiated many times
+#[derive(Clone, Debug)]
+pub struct GltfData {
+	pub doc: Document,
+	pub buffers: Vec<gltf::buffer::Data>,
+	pub images: Vec<image::Data>,
+}
+
+impl GltfData {
+	pub fn from_path(path: &Path) -> Result<Self, Box<StdError>> {
+		let (doc, buffers, images) = gltf::import(path)?;
+		Ok(Self { doc, buffers, images })
+	}
+
+	pub fn from_slice(data: &[u8]) -> Result<Self, Box<StdError>> {
+		let (doc, buffers, images) = gltf::import_slice(data)?;
+		Ok(Self { doc, buffers, images })
+	}
+
+	pub fn memory_size(&self) -> usize {
+		let buffers: usize = self.buffers.iter().map(|buffer| buffer.len()).sum();
+		let images: usize = self.images.iter().map(|image| image.pixels.len()).sum();
+		buffers + images
+	}
+}
+
 pub fn load_gltf(world: &mut World, path: PathBuf) -> Result<Entity, Box<StdError>> {
+	let data = GltfData::from_path(&path)?;
+	load_gltf_data(world, data)
+}
+
+pub fn load_gltf_data(world: &mut World, data: GltfData) -> Result<Entity, Box<StdError>> {
 	println!("++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++=");
 
-	let (doc, buffers, images) = gltf::import(path.clone())?;
+	let GltfData { doc, buffers, images } = data;
 
 	let images: Vec<TextureData> = images
 		.iter()
@@ -157,7 +187,6 @@ pub fn load_gltf(world: &mut World, path: PathBuf) -> Result<Entity, Box<StdErro
 		doc,
 		buffers,
 		images,
-		path: path.clone(),
 	};
 
 	let root = world.create_entity()
@@ -278,9 +307,9 @@ fn load_node(world: &mut World, node: &gltf::Node, context: &Context, depth: i32
 							Semantic::Tangents => {
 								let tangents: Vec<_> = reader.read_tangents()
 									.expect("cant find tangents")
-									.map(|v| Vector3::new( v[0], v[1], v[2] ) )
+									.map(|v| Vector4::new( v[0], v[1], v[2], v[3] ) )
 									.collect();
-								BufferData::Vector3(tangents)
+								BufferData::Vector4(tangents)
 							}
 							Semantic::Weights(_) => {unimplemented!()}
 						};
@@ -299,6 +328,18 @@ fn load_node(world: &mut World, node: &gltf::Node, context: &Context, depth: i32
 				let mut geom = BufferGeometry::new();
 				attributes.into_iter().for_each(|e| {geom.add_buffer_attribute(e);} );
 				indices.map(|data| {geom.set_indices(data)} );
+
+				if primitive.material().normal_texture().is_some() && !geom.has_attribute(BufferType::Tangent) {
+					if geom.indices.len() == 0 {
+						geom.gen_indices().ok();
+					}
+					if !geom.has_attribute(BufferType::Normal) {
+						geom.generate_normals();
+					}
+					if let Err(err) = geom.generate_tangents() {
+						println!("{}", err);
+					}
+				}
 				(geom, primitive.material().index())
 			})
 			.collect();
@@ -1,8 +1,12 @@
 extern crate obj;
 
-use core::{BufferGeometry, BufferData, BufferGroup, BufferType};
+use core::{BufferGeometry, BufferData, BufferGroup, BufferType, MeshBake};
 use math::{Vector3, Vector2};
-use self::obj::{Obj, SimplePolygon, IndexTuple};
+use self::obj::{Material, Mtl, Obj, SimplePolygon, IndexTuple};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::io::BufReader;
 use std::path::Path;
 
 #[derive(Clone, Debug)]
@@ -56,77 +60,236 @@ fn add_elem (
 
 #[allow(dead_code)]
 pub fn load_obj( path: &Path ) -> Result<Vec<BufferGeometry>, String>{
-	match Obj::<SimplePolygon>::load(path) {
-		Ok(obj_data) => {
-
-			let mut result = Vec::new();
-
-			for object in &obj_data.objects  {
-				let mut geom = BufferGeometry::new();
-				geom.name = object.name.clone();
-
-				let length = obj_data.position.len();
-				let mut indices = Vec::with_capacity(length*4);
-				let mut data_map = vec![None; length];
-				let mut data_order = Vec::with_capacity(length);
-
-				println!("name: {}, groups: {}", object.name, object.groups.len());
-
-				for group in &object.groups {
-					// unimplemented!();
-					println!("name: {}, index: {}, polys: {}", group.name, group.index, group.polys.len());
-					let mut buffer_group = BufferGroup {
-						name: Some(group.name.clone()),
-						count: 0,
-						material_index: group.index,
-						start: indices.len(),
-					};
-
-					for poly in &group.polys {
-						match poly.len() {
-							4 => {
-								add_elem(&poly[0], &mut data_map, &mut data_order, &mut indices, &obj_data);
-								add_elem(&poly[1], &mut data_map, &mut data_order, &mut indices, &obj_data);
-								add_elem(&poly[2], &mut data_map, &mut data_order, &mut indices, &obj_data);
-
-								add_elem(&poly[2], &mut data_map, &mut data_order, &mut indices, &obj_data);
-								add_elem(&poly[3], &mut data_map, &mut data_order, &mut indices, &obj_data);
-								add_elem(&poly[0], &mut data_map, &mut data_order, &mut indices, &obj_data);
-							}
-							3 => {
-								add_elem(&poly[0], &mut data_map, &mut data_order, &mut indices, &obj_data);
-								add_elem(&poly[1], &mut data_map, &mut data_order, &mut indices, &obj_data);
-								add_elem(&poly[2], &mut data_map, &mut data_order, &mut indices, &obj_data);
-							}
-							_ => {}
-						}
-					}
+	let data = std::fs::read(path).or(Err(format!("cant read file: {}", path.to_string_lossy())))?;
+	let mtls = read_material_libs(&data, path.parent().unwrap_or(Path::new("")));
+	let mtls: Vec<&[u8]> = mtls.iter().map(|mtl| &mtl[..]).collect();
+	load_obj_from_bytes(&data, &mtls)
+}
 
-					buffer_group.count = indices.len() - buffer_group.start;
-					geom.groups.push(buffer_group);
-				}
+// `mtls` are the material libraries referenced by the obj,
+// materials are only used to detect normal maps
+#[allow(dead_code)]
+pub fn load_obj_from_bytes( data: &[u8], mtls: &[&[u8]] ) -> Result<Vec<BufferGeometry>, String>{
+	Ok(build_obj(&parse_obj(data, mtls)?))
+}
+
+// names of material libraries from `mtllib`, relative to the obj
+pub fn obj_material_libs(data: &[u8]) -> Vec<String> {
+	String::from_utf8_lossy(data)
+		.lines()
+		.filter_map(|line| {
+			let mut words = line.split_whitespace();
+			match words.next() {
+				Some("mtllib") => Some(words.map(|e| e.to_string()).collect::<Vec<_>>()),
+				_ => None,
+			}
+		})
+		.flatten()
+		.collect()
+}
+
+// material libraries of an obj from `dir`, missing ones are reported and skipped
+pub fn read_material_libs(data: &[u8], dir: &Path) -> Vec<Vec<u8>> {
+	obj_material_libs(data)
+		.iter()
+		.filter_map(|name| match std::fs::read(dir.join(name)) {
+			Ok(mtl) => Some(mtl),
+			Err(err) => {
+				println!("cant load {}: {}", name, err);
+				None
+			}
+		})
+		.collect()
+}
 
-				let positions = data_order.iter().map(|i| data_map[*i].as_ref().unwrap().position.clone() ).collect();
-				geom.create_buffer_attribute(BufferType::Position, BufferData::Vector3(positions));
+fn parse_obj( data: &[u8], mtls: &[&[u8]] ) -> Result<Obj<'static, SimplePolygon>, String> {
+	let mut obj_data = Obj::<SimplePolygon>::load_buf(&mut BufReader::new(data)).or_else(|err| Err(format!("{:?}", err)))?;
 
-				let elem = data_map[data_order[0]].as_ref().unwrap();
+	let materials: HashMap<String, Material> = mtls
+		.iter()
+		.flat_map(|mtl| Mtl::load(&mut BufReader::new(*mtl)).materials)
+		.map(|material| (material.name.clone(), material))
+		.collect();
+
+	for group in obj_data.objects.iter_mut().flat_map(|object| object.groups.iter_mut()) {
+		let material = group.material.as_ref().and_then(|material| materials.get(&material.name));
+		if let Some(material) = material {
+			group.material = Some(Cow::Owned(material.clone()));
+		}
+	}
 
-				if elem.normal.is_some() {
-					let normal = data_order.iter().map(|i| data_map[*i].as_ref().unwrap().normal.as_ref().unwrap().clone() ).collect();
-					geom.create_buffer_attribute(BufferType::Normal, BufferData::Vector3(normal));
+	Ok(obj_data)
+}
+
+fn build_obj( obj_data: &Obj<SimplePolygon> ) -> Vec<BufferGeometry> {
+	let mut result = Vec::new();
+
+	for object in &obj_data.objects  {
+		let mut geom = BufferGeometry::new();
+		geom.name = object.name.clone();
+
+		let length = obj_data.position.len();
+		let mut indices = Vec::with_capacity(length*4);
+		let mut data_map = vec![None; length];
+		let mut data_order = Vec::with_capacity(length);
+
+		println!("name: {}, groups: {}", object.name, object.groups.len());
+
+		for group in &object.groups {
+			// unimplemented!();
+			println!("name: {}, index: {}, polys: {}", group.name, group.index, group.polys.len());
+			let mut buffer_group = BufferGroup {
+				name: Some(group.name.clone()),
+				count: 0,
+				material_index: group.index,
+				start: indices.len(),
+			};
+
+			for poly in &group.polys {
+				match poly.len() {
+					4 => {
+						add_elem(&poly[0], &mut data_map, &mut data_order, &mut indices, obj_data);
+						add_elem(&poly[1], &mut data_map, &mut data_order, &mut indices, obj_data);
+						add_elem(&poly[2], &mut data_map, &mut data_order, &mut indices, obj_data);
+
+						add_elem(&poly[2], &mut data_map, &mut data_order, &mut indices, obj_data);
+						add_elem(&poly[3], &mut data_map, &mut data_order, &mut indices, obj_data);
+						add_elem(&poly[0], &mut data_map, &mut data_order, &mut indices, obj_data);
+					}
+					3 => {
+						add_elem(&poly[0], &mut data_map, &mut data_order, &mut indices, obj_data);
+						add_elem(&poly[1], &mut data_map, &mut data_order, &mut indices, obj_data);
+						add_elem(&poly[2], &mut data_map, &mut data_order, &mut indices, obj_data);
+					}
+					_ => {}
 				}
+			}
+
+			buffer_group.count = indices.len() - buffer_group.start;
+			geom.groups.push(buffer_group);
+		}
 
-				if elem.uv.is_some() {
-					let normal = data_order.iter().map(|i| data_map[*i].as_ref().unwrap().uv.as_ref().unwrap().clone() ).collect();
-					geom.create_buffer_attribute(BufferType::UV(0), BufferData::Vector2(normal));
+		let positions = data_order.iter().map(|i| data_map[*i].as_ref().unwrap().position.clone() ).collect();
+		geom.create_buffer_attribute(BufferType::Position, BufferData::Vector3(positions));
+
+		let elem = data_map[data_order[0]].as_ref().unwrap();
+
+		if elem.normal.is_some() {
+			let normal = data_order.iter().map(|i| data_map[*i].as_ref().unwrap().normal.as_ref().unwrap().clone() ).collect();
+			geom.create_buffer_attribute(BufferType::Normal, BufferData::Vector3(normal));
+		}
+
+		if elem.uv.is_some() {
+			let normal = data_order.iter().map(|i| data_map[*i].as_ref().unwrap().uv.as_ref().unwrap().clone() ).collect();
+			geom.create_buffer_attribute(BufferType::UV(0), BufferData::Vector2(normal));
+		}
+
+		geom.set_indices(indices);
+
+		let normal_map = object.groups.iter().any(|group| {
+			group.material.as_ref().map_or(false, |mtl| mtl.map_bump.is_some())
+		});
+		if normal_map {
+			if !geom.has_attribute(BufferType::Normal) {
+				geom.generate_normals();
+			}
+			if let Err(err) = geom.generate_tangents() {
+				println!("{}: {}", object.name, err);
+			}
+		}
+
+		result.push(geom);
+	}
+	result
+}
+
+// optimized obj for the resource-manager build, `mtllib` and material names are kept
+pub fn bake_obj(data: &[u8], mtls: &[&[u8]], bake: &MeshBake) -> Result<Vec<u8>, String> {
+	let obj_data = parse_obj(data, mtls)?;
+	let mut geometries = build_obj(&obj_data);
+	geometries.iter_mut().for_each(|geom| geom.optimize(bake));
+
+	let materials: Vec<Vec<Option<String>>> = obj_data
+		.objects
+		.iter()
+		.map(|object| object.groups.iter().map(|group| group.material.as_ref().map(|e| e.name.clone())).collect())
+		.collect();
+	Ok(write_obj(&geometries, &obj_data.material_libs, &materials).into_bytes())
+}
+
+// every vertex gets own position, uv and normal index, so the vertex order survives reloading,
+// group material indices are written as repeated `usemtl`;
+// `materials` are names per geometry and group, missing ones get generated names
+pub fn write_obj(geometries: &[BufferGeometry], material_libs: &[String], materials: &[Vec<Option<String>>]) -> String {
+	let mut out = String::new();
+	let mut offset = 1;
+
+	material_libs.iter().for_each(|lib| writeln!(out, "mtllib {}", lib).unwrap());
+
+	for (geom_index, geom) in geometries.iter().enumerate() {
+		let positions = match geom.get_attribute(BufferType::Position).map(|e| &e.data) {
+			Some(BufferData::Vector3(positions)) if geom.indices.len() > 0 => positions,
+			_ => continue,
+		};
+		let normals = match geom.get_attribute(BufferType::Normal).map(|e| &e.data) {
+			Some(BufferData::Vector3(normals)) => Some(normals),
+			_ => None,
+		};
+		let uvs = match geom.get_attribute(BufferType::UV(0)).map(|e| &e.data) {
+			Some(BufferData::Vector2(uvs)) => Some(uvs),
+			_ => None,
+		};
+
+		let name = if geom.name.is_empty() { "default" } else { &geom.name[..] };
+		writeln!(out, "o {}", name).unwrap();
+		positions.iter().for_each(|v| writeln!(out, "v {} {} {}", v.x, v.y, v.z).unwrap());
+		uvs.iter().flat_map(|e| e.iter()).for_each(|v| writeln!(out, "vt {} {}", v.x, v.y).unwrap());
+		normals.iter().flat_map(|e| e.iter()).for_each(|v| writeln!(out, "vn {} {} {}", v.x, v.y, v.z).unwrap());
+
+		let groups = match geom.groups.len() {
+			0 => vec![BufferGroup {
+				start: 0,
+				count: geom.indices.len(),
+				material_index: 0,
+				name: None,
+			}],
+			_ => geom.groups.clone(),
+		};
+
+		for (i, group) in groups.iter().enumerate() {
+			let group_name = group.name.as_ref().map_or("default", |e| &e[..]);
+			let next_index = groups.get(i + 1).filter(|e| e.name == group.name).map(|e| e.material_index);
+			let material = materials.get(geom_index).and_then(|e| e.get(i)).and_then(|e| e.as_ref());
+			// `usemtl` on a group that has one starts the next index
+			if group.material_index == 0 {
+				writeln!(out, "g {}", group_name).unwrap();
+			}
+			if material.is_some() || group.material_index > 0 || next_index == Some(group.material_index + 1) {
+				match material {
+					Some(material) => writeln!(out, "usemtl {}", material),
+					None => writeln!(out, "usemtl {}_{}", group_name, group.material_index),
 				}
+				.unwrap();
+			}
 
-				geom.set_indices(indices);
-				result.push(geom);
+			for tri in geom.indices[group.start..group.start + group.count].chunks(3) {
+				out.push_str("f");
+				for v in tri {
+					let v = *v as usize + offset;
+					match (uvs.is_some(), normals.is_some()) {
+						(true, true) => write!(out, " {}/{}/{}", v, v, v),
+						(true, false) => write!(out, " {}/{}", v, v),
+						(false, true) => write!(out, " {}//{}", v, v),
+						(false, false) => write!(out, " {}", v),
+					}
+					.unwrap();
+				}
+				out.push_str("\n");
 			}
-			Ok(result)
 		}
 
-		Err(err) => {Err(format!("{:?}", err))}
+		offset += positions.len();
 	}
+
+	out
 }
@@ -9,4 +9,5 @@ pub mod core;
 pub mod components;
 #[macro_use] pub mod render;
 pub mod helpers;
+pub mod resources;
 
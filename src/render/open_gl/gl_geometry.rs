@@ -5,7 +5,7 @@ extern crate uuid;
 use self::byteorder::{LittleEndian, WriteBytesExt};
 use self::gl::types::*;
 use self::uuid::Uuid;
-use core::{BufferGeometry, BufferData, BufferType};
+use core::{BufferGeometry, BufferData, BufferType, DrawMode};
 use std::collections::HashMap;
 use std::mem;
 use std::os::raw::c_void;
@@ -41,6 +41,101 @@ impl Default for Buffers {
 	}
 }
 
+pub fn to_gl_draw_mode(draw_mode: DrawMode) -> GLenum {
+	match draw_mode {
+		DrawMode::Points => gl::POINTS,
+		DrawMode::Lines => gl::LINES,
+		DrawMode::LineStrip => gl::LINE_STRIP,
+		DrawMode::LineLoop => gl::LINE_LOOP,
+		DrawMode::Triangles => gl::TRIANGLES,
+		DrawMode::TriangleStrip => gl::TRIANGLE_STRIP,
+		DrawMode::TriangleFan => gl::TRIANGLE_FAN,
+	}
+}
+
+// writes item `i` of `data` as little endian bytes
+pub fn write_buffer_item(buffer: &mut Vec<u8>, data: &BufferData, i: usize) {
+	match data {
+		BufferData::Matrix4(v) => {
+			buffer.write_f32::<LittleEndian>(v[i][0]).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i][1]).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i][2]).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i][3]).unwrap();
+
+			buffer.write_f32::<LittleEndian>(v[i][4]).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i][5]).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i][6]).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i][7]).unwrap();
+
+			buffer.write_f32::<LittleEndian>(v[i][8]).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i][9]).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i][10]).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i][11]).unwrap();
+
+			buffer.write_f32::<LittleEndian>(v[i][12]).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i][13]).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i][14]).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i][15]).unwrap();
+		}
+		BufferData::Matrix3(v) => {
+			buffer.write_f32::<LittleEndian>(v[i][0]).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i][1]).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i][2]).unwrap();
+
+			buffer.write_f32::<LittleEndian>(v[i][3]).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i][4]).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i][5]).unwrap();
+
+			buffer.write_f32::<LittleEndian>(v[i][6]).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i][7]).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i][8]).unwrap();
+		}
+		BufferData::Matrix2(v) => {
+			buffer.write_f32::<LittleEndian>(v[i][0]).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i][1]).unwrap();
+
+			buffer.write_f32::<LittleEndian>(v[i][2]).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i][3]).unwrap();
+		}
+		BufferData::Vector4(v) => {
+			buffer.write_f32::<LittleEndian>(v[i].x).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i].y).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i].z).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i].w).unwrap();
+		}
+		BufferData::Vector3(v) => {
+			buffer.write_f32::<LittleEndian>(v[i].x).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i].y).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i].z).unwrap();
+		}
+		BufferData::Vector2(v) => {
+			buffer.write_f32::<LittleEndian>(v[i].x).unwrap();
+			buffer.write_f32::<LittleEndian>(v[i].y).unwrap();
+		}
+		BufferData::F32(v) => {
+			buffer.write_f32::<LittleEndian>(v[i]).unwrap();
+		}
+		BufferData::I32(v) => {
+			buffer.write_i32::<LittleEndian>(v[i]).unwrap();
+		}
+		BufferData::U32(v) => {
+			buffer.write_u32::<LittleEndian>(v[i]).unwrap();
+		}
+		BufferData::I16(v) => {
+			buffer.write_i16::<LittleEndian>(v[i]).unwrap();
+		}
+		BufferData::U16(v) => {
+			buffer.write_u16::<LittleEndian>(v[i]).unwrap();
+		}
+		BufferData::I8(v) => {
+			buffer.write_i8(v[i]).unwrap();
+		}
+		BufferData::U8(v) => {
+			buffer.write_u8(v[i]).unwrap();
+		}
+	}
+}
+
 #[allow(dead_code)]
 pub trait GLGeometry {
 	fn bind(&self, hash_map: &mut VertexArraysIDs);
@@ -76,85 +171,7 @@ pub trait GLGeometry {
 
 		for i in 0..positions_len {
 			for buffer_data in buffers.iter() {
-				match &buffer_data.data {
-					BufferData::Matrix4(v) => {
-						buffer.write_f32::<LittleEndian>(v[i][0]).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i][1]).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i][2]).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i][3]).unwrap();
-
-						buffer.write_f32::<LittleEndian>(v[i][4]).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i][5]).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i][6]).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i][7]).unwrap();
-
-						buffer.write_f32::<LittleEndian>(v[i][8]).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i][9]).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i][10]).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i][11]).unwrap();
-
-						buffer.write_f32::<LittleEndian>(v[i][12]).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i][13]).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i][14]).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i][15]).unwrap();
-					}
-					BufferData::Matrix3(v) => {
-						buffer.write_f32::<LittleEndian>(v[i][0]).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i][1]).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i][2]).unwrap();
-
-						buffer.write_f32::<LittleEndian>(v[i][3]).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i][4]).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i][5]).unwrap();
-
-						buffer.write_f32::<LittleEndian>(v[i][6]).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i][7]).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i][8]).unwrap();
-					}
-					BufferData::Matrix2(v) => {
-						buffer.write_f32::<LittleEndian>(v[i][0]).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i][1]).unwrap();
-
-						buffer.write_f32::<LittleEndian>(v[i][2]).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i][3]).unwrap();
-					}
-					BufferData::Vector4(v) => {
-						buffer.write_f32::<LittleEndian>(v[i].x).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i].y).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i].z).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i].w).unwrap();
-					}
-					BufferData::Vector3(v) => {
-						buffer.write_f32::<LittleEndian>(v[i].x).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i].y).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i].z).unwrap();
-					}
-					BufferData::Vector2(v) => {
-						buffer.write_f32::<LittleEndian>(v[i].x).unwrap();
-						buffer.write_f32::<LittleEndian>(v[i].y).unwrap();
-					}
-					BufferData::F32(v) => {
-						buffer.write_f32::<LittleEndian>(v[i]).unwrap();
-					}
-					BufferData::I32(v) => {
-						buffer.write_i32::<LittleEndian>(v[i]).unwrap();
-					}
-					BufferData::U32(v) => {
-						buffer.write_u32::<LittleEndian>(v[i]).unwrap();
-					}
-					BufferData::I16(v) => {
-						buffer.write_i16::<LittleEndian>(v[i]).unwrap();
-					}
-					BufferData::U16(v) => {
-						buffer.write_u16::<LittleEndian>(v[i]).unwrap();
-					}
-					BufferData::I8(v) => {
-						buffer.write_i8(v[i]).unwrap();
-					}
-					BufferData::U8(v) => {
-						buffer.write_u8(v[i]).unwrap();
-					}
-				}
+				write_buffer_item(&mut buffer, &buffer_data.data, i);
 			}
 		}
 
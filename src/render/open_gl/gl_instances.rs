@@ -0,0 +1,145 @@
+extern crate gl;
+extern crate uuid;
+
+use self::gl::types::*;
+use self::uuid::Uuid;
+use core::{attribute_columns, BufferData, Instances, INSTANCE_ATTRIBUTE_LOCATION};
+use std::collections::HashMap;
+use std::os::raw::c_void;
+
+use super::gl_geometry::write_buffer_item;
+
+pub type GLInstanceIDs = HashMap<Uuid, InstanceBuffer>;
+
+// location, components, type and byte offset of one attribute column
+type Column = (GLuint, GLint, GLenum, usize);
+
+#[derive(Debug)]
+pub struct InstanceBuffer {
+	array_buffer: GLuint,
+	version: usize,
+	stride: usize,
+	columns: Vec<Column>,
+	pub count: usize,
+}
+
+impl Drop for InstanceBuffer {
+	fn drop(&mut self) {
+		gl_call!({
+			gl::DeleteBuffers(1, &self.array_buffer);
+		});
+	}
+}
+
+impl InstanceBuffer {
+	// sets instance attributes on the bound vertex array
+	pub fn bind(&self) {
+		gl_call!({
+			gl::BindBuffer(gl::ARRAY_BUFFER, self.array_buffer);
+		});
+		for (location, components, val_type, offset) in self.columns.iter() {
+			gl_call!({
+				gl::VertexAttribPointer(
+					*location,
+					*components,
+					*val_type,
+					gl::FALSE,
+					self.stride as GLsizei,
+					*offset as *const c_void,
+				);
+				gl::VertexAttribDivisor(*location, 1);
+				gl::EnableVertexAttribArray(*location);
+			});
+		}
+	}
+
+	// vertex arrays are shared with not instanced draws
+	pub fn unbind(&self) {
+		for (location, _, _, _) in self.columns.iter() {
+			gl_call!({
+				gl::VertexAttribDivisor(*location, 0);
+				gl::DisableVertexAttribArray(*location);
+			});
+		}
+	}
+}
+
+pub trait GLInstances {
+	fn update(&self, hash_map: &mut GLInstanceIDs);
+}
+
+impl GLInstances for Instances {
+	// `Instances::is_valid` has to be checked before
+	fn update(&self, hash_map: &mut GLInstanceIDs) {
+		if let Some(buffer) = hash_map.get(&self.uuid) {
+			if buffer.version == self.get_version() && buffer.count == self.len() {
+				return;
+			}
+		}
+
+		let transforms = BufferData::Matrix4(self.get_transforms().to_vec());
+		let attributes: Vec<_> = Some(&transforms).into_iter().chain(self.iter_attributes().map(|attr| &attr.data)).collect();
+
+		let mut columns = Vec::new();
+		let mut offset = 0;
+		let mut location = INSTANCE_ATTRIBUTE_LOCATION;
+		for data in attributes.iter() {
+			let count = attribute_columns(data);
+			let components = data.item_size() / count;
+			for _ in 0..count {
+				columns.push((location as GLuint, components as GLint, gl_type(data), offset));
+				offset += components * data.elem_byte_len() / data.item_size();
+				location += 1;
+			}
+		}
+		let stride = offset;
+
+		let mut buffer: Vec<u8> = Vec::with_capacity(stride * self.len());
+		for i in 0..self.len() {
+			for data in attributes.iter() {
+				write_buffer_item(&mut buffer, data, i);
+			}
+		}
+
+		let instance_buffer = hash_map.entry(self.uuid).or_insert_with(|| {
+			let mut array_buffer = 0;
+			gl_call!({
+				gl::GenBuffers(1, &mut array_buffer);
+			});
+			InstanceBuffer {
+				array_buffer,
+				version: 0,
+				stride: 0,
+				columns: Vec::new(),
+				count: 0,
+			}
+		});
+
+		gl_call!({
+			gl::BindBuffer(gl::ARRAY_BUFFER, instance_buffer.array_buffer);
+			gl::BufferData(
+				gl::ARRAY_BUFFER,
+				buffer.len() as GLsizeiptr,
+				if buffer.len() > 0 { buffer.as_ptr() as *const c_void } else { std::ptr::null() },
+				gl::DYNAMIC_DRAW,
+			);
+		});
+
+		instance_buffer.version = self.get_version();
+		instance_buffer.stride = stride;
+		instance_buffer.columns = columns;
+		instance_buffer.count = self.len();
+	}
+}
+
+fn gl_type(data: &BufferData) -> GLenum {
+	match data {
+		BufferData::I32(_) => gl::INT,
+		BufferData::U32(_) => gl::UNSIGNED_INT,
+		BufferData::I16(_) => gl::SHORT,
+		BufferData::U16(_) => gl::UNSIGNED_SHORT,
+		BufferData::I8(_) => gl::BYTE,
+		BufferData::U8(_) => gl::UNSIGNED_BYTE,
+		_ => gl::FLOAT,
+	}
+}
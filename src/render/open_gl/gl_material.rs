@@ -11,7 +11,8 @@ use super::{
 };
 use std::collections::HashMap;
 
-pub type GLMaterialIDs = HashMap<Uuid, GLShaderProgramID>;
+// programs by shader and instance definitions, instanced and plain draws of a material use separate programs
+pub type GLMaterialIDs = HashMap<(Uuid, Vec<(String, usize)>), GLShaderProgramID>;
 
 pub trait GLMaterial
 where
@@ -30,12 +31,14 @@ impl GLMaterial for Material {
 	fn bind(&mut self, bind_context: &mut BindContext) {
 		{
 			let mut shader_program = self.get_shader_program().unwrap();
+			let uuid = shader_program.get_uuid();
+			let key = (uuid, bind_context.instance_definitions.to_vec());
 
 			if shader_program.is_need_update() {
-				bind_context.gl_material_ids.remove(&shader_program.get_uuid());
+				bind_context.gl_material_ids.retain(|(program_uuid, _), _| *program_uuid != uuid);
 				shader_program.set_need_update(false);
 			} else {
-				match bind_context.gl_material_ids.get_mut(&shader_program.get_uuid()) {
+				match bind_context.gl_material_ids.get_mut(&key) {
 					None => {}
 					Some(program) => {
 						gl_call!({
@@ -58,6 +61,16 @@ impl GLMaterial for Material {
 				shader_program.set_uniform(key, val.clone());
 			});
 			let mut program = compile_shader_program(&*shader_program, bind_context);
+
+			if bind_context.strict {
+				program
+					.reflection
+					.validate(&self.uniforms, bind_context.geometry)
+					.iter()
+					.for_each(|e| println!("WARNING::SHADER::VALIDATION: {}: {}", shader_program.get_src(), e));
+			}
+			shader_program.set_reflection(Some(program.reflection.clone()));
+
 			set_uniforms(
 				&self.uniforms,
 				&mut *shader_program,
@@ -66,7 +79,7 @@ impl GLMaterial for Material {
 				true,
 			);
 
-			bind_context.gl_material_ids.insert(shader_program.get_uuid(), program);
+			bind_context.gl_material_ids.insert(key, program);
 		}
 
 		self.bind(bind_context);
@@ -0,0 +1,192 @@
+extern crate regex;
+
+use self::regex::Regex;
+use helpers::find_file;
+use std::collections::HashSet;
+
+lazy_static! {
+	static ref RE_DIRECTIVE: Regex = Regex::new(r"^\s*#\s*(\w+)\s*(.*?)\s*$").unwrap();
+	static ref RE_INCLUDE: Regex = Regex::new(r"^<(\S+)>$").unwrap();
+	static ref RE_NAME: Regex = Regex::new(r"^(\w+)").unwrap();
+}
+
+pub const SHADER_DIRS: &[&str] = &["res/shaders"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BlockState {
+	Active,
+	Inactive,
+	// condition can't be resolved here (#if expressions), leave it to the glsl compiler
+	Unknown,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Block {
+	state: BlockState,
+	taken: bool,
+}
+
+// Expands `#include <name>` directives. `#ifdef` / `#ifndef` blocks are resolved
+// against the known defines, so includes inside disabled blocks are skipped and
+// classic include guards work. Files marked with `#pragma once` are included only once.
+pub struct ShaderPreprocessor<'a> {
+	dirs: &'a [&'a str],
+	defines: HashSet<String>,
+	included_once: HashSet<String>,
+	include_stack: Vec<String>,
+}
+
+impl<'a> ShaderPreprocessor<'a> {
+	pub fn new(dirs: &'a [&'a str]) -> Self {
+		Self {
+			dirs,
+			defines: HashSet::new(),
+			included_once: HashSet::new(),
+			include_stack: Vec::new(),
+		}
+	}
+
+	pub fn is_defined(&self, name: &str) -> bool {
+		self.defines.contains(name)
+	}
+
+	pub fn process(&mut self, name: &str, code: &str) -> Result<String, String> {
+		if self.include_stack.iter().any(|e| e == name) {
+			let mut chain = self.include_stack.clone();
+			chain.push(name.to_string());
+			return Err(format!("include cycle: {}", chain.join(" -> ")));
+		}
+
+		self.include_stack.push(name.to_string());
+		let res = self.process_lines(name, code);
+		self.include_stack.pop();
+		res
+	}
+
+	pub fn process_file(&mut self, name: &str) -> Result<String, String> {
+		let code = read_include(self.dirs, name)?;
+		self.process(name, &code)
+	}
+
+	fn process_lines(&mut self, name: &str, code: &str) -> Result<String, String> {
+		let mut blocks: Vec<Block> = Vec::new();
+		let mut result = String::with_capacity(code.len());
+
+		for line in code.lines() {
+			let parent = blocks.last().map_or(BlockState::Active, |b| b.state);
+
+			let cap = match RE_DIRECTIVE.captures(line) {
+				None => {
+					push_line(&mut result, line);
+					continue;
+				}
+				Some(cap) => cap,
+			};
+
+			let directive = &cap[1];
+			let args = &cap[2];
+
+			match directive {
+				"ifdef" | "ifndef" => {
+					let state = match parent {
+						BlockState::Inactive => BlockState::Inactive,
+						_ => {
+							let defined = self.is_defined(&first_word(args));
+							if defined == (directive == "ifdef") {
+								BlockState::Active
+							} else {
+								BlockState::Inactive
+							}
+						}
+					};
+					blocks.push(Block {
+						state,
+						taken: state == BlockState::Active,
+					});
+				}
+				"if" => {
+					let state = match parent {
+						BlockState::Inactive => BlockState::Inactive,
+						_ => BlockState::Unknown,
+					};
+					blocks.push(Block { state, taken: false });
+				}
+				"elif" | "else" => {
+					let block = blocks
+						.pop()
+						.ok_or(format!("{}: #{} without #if", name, directive))?;
+					let parent = blocks.last().map_or(BlockState::Active, |b| b.state);
+
+					let state = match (parent, block.state, block.taken) {
+						(BlockState::Inactive, _, _) => BlockState::Inactive,
+						(_, _, true) => BlockState::Inactive,
+						(_, BlockState::Unknown, _) => BlockState::Unknown,
+						_ if directive == "elif" => BlockState::Unknown,
+						_ => BlockState::Active,
+					};
+					blocks.push(Block {
+						state,
+						taken: block.taken || state == BlockState::Active,
+					});
+				}
+				"endif" => {
+					blocks.pop().ok_or(format!("{}: #endif without #if", name))?;
+				}
+				"define" if parent == BlockState::Active => {
+					self.defines.insert(first_word(args));
+				}
+				"undef" if parent == BlockState::Active => {
+					self.defines.remove(&first_word(args));
+				}
+				"pragma" if args == "once" => {
+					if parent != BlockState::Inactive {
+						self.included_once.insert(name.to_string());
+					}
+					continue;
+				}
+				"include" => {
+					if parent == BlockState::Inactive {
+						continue;
+					}
+					let include = RE_INCLUDE
+						.captures(args)
+						.ok_or(format!("{}: wrong include: {}", name, line))?[1]
+						.to_string();
+
+					if self.included_once.contains(&include) {
+						continue;
+					}
+
+					let code = self.process_file(&include)?;
+					result += &code;
+					continue;
+				}
+				_ => {}
+			}
+
+			push_line(&mut result, line);
+		}
+
+		if !blocks.is_empty() {
+			return Err(format!("{}: unterminated #if block", name));
+		}
+
+		Ok(result)
+	}
+}
+
+fn push_line(result: &mut String, line: &str) {
+	*result += line;
+	*result += "\n";
+}
+
+fn first_word(args: &str) -> String {
+	RE_NAME
+		.captures(args)
+		.map_or("".to_string(), |cap| cap[1].to_string())
+}
+
+pub fn read_include(dirs: &[&str], name: &str) -> Result<String, String> {
+	let path = find_file(dirs, &(name.to_string() + ".glsl"))?;
+	std::fs::read_to_string(&path).or(Err(format!("cant read shader: {}", path.to_string_lossy())))
+}
@@ -1,30 +1,29 @@
 extern crate gl;
 extern crate heck;
-extern crate regex;
 extern crate uuid;
 
 use self::gl::types::*;
 
 use self::heck::ShoutySnakeCase;
-use self::regex::Regex;
+use super::gl_shader_preprocessor::{read_include, ShaderPreprocessor, SHADER_DIRS};
 use super::gl_texture::{GLTexture, GLTextureIDs};
+use super::gl_uniform_buffer::bind_uniform_blocks;
 use super::BindContext;
-use core::{ShaderProgram, ShaderTag, Uniform, UniformName};
-use helpers::{find_file, read_to_string};
+use core::{
+	ActiveAttribute, ActiveUniform, ShaderDataType, ShaderProgram, ShaderReflection, ShaderSource, ShaderTag, Uniform,
+	UniformName,
+};
 use std::collections::HashMap;
 use std::ffi::CString;
 use std::ptr;
 use std::str;
 
-lazy_static! {
-	static ref RE_INCLUDE: Regex = Regex::new(r"#include\s+<(\S+)>").unwrap();
-}
-
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ProgramType {
 	None,
 	Vertex,
 	Fragment,
+	Geometry,
 }
 
 #[derive(Debug)]
@@ -37,9 +36,11 @@ pub struct UniformLocation {
 pub struct GLShaderProgramID {
 	pub fs_source: String,
 	pub vs_source: String,
+	pub gs_source: Option<String>,
 	pub id: GLuint,
 	pub uniform_locations: HashMap<UniformName, UniformLocation>,
 	pub texture_slots: i32,
+	pub reflection: ShaderReflection,
 }
 
 impl Drop for GLShaderProgramID {
@@ -106,6 +107,117 @@ pub fn set_uniform(uniform: Uniform, loc: &UniformLocation, texture_store: &mut
 				gl::Uniform1ui(loc.location, data);
 			});
 		}
+		Uniform::Bool(data) => {
+			gl_call!({
+				gl::Uniform1i(loc.location, data as i32);
+			});
+		}
+		Uniform::IVec2(data) => {
+			gl_call!({
+				gl::Uniform2iv(loc.location, 1, &data[0] as *const i32);
+			});
+		}
+		Uniform::IVec3(data) => {
+			gl_call!({
+				gl::Uniform3iv(loc.location, 1, &data[0] as *const i32);
+			});
+		}
+		Uniform::IVec4(data) => {
+			gl_call!({
+				gl::Uniform4iv(loc.location, 1, &data[0] as *const i32);
+			});
+		}
+		Uniform::UVec2(data) => {
+			gl_call!({
+				gl::Uniform2uiv(loc.location, 1, &data[0] as *const u32);
+			});
+		}
+		Uniform::UVec3(data) => {
+			gl_call!({
+				gl::Uniform3uiv(loc.location, 1, &data[0] as *const u32);
+			});
+		}
+		Uniform::UVec4(data) => {
+			gl_call!({
+				gl::Uniform4uiv(loc.location, 1, &data[0] as *const u32);
+			});
+		}
+		Uniform::FloatArray(data) => {
+			if data.len() == 0 {
+				return;
+			}
+			gl_call!({
+				gl::Uniform1fv(loc.location, data.len() as i32, &data[0] as *const f32);
+			});
+		}
+		Uniform::IntArray(data) => {
+			if data.len() == 0 {
+				return;
+			}
+			gl_call!({
+				gl::Uniform1iv(loc.location, data.len() as i32, &data[0] as *const i32);
+			});
+		}
+		Uniform::UIntArray(data) => {
+			if data.len() == 0 {
+				return;
+			}
+			gl_call!({
+				gl::Uniform1uiv(loc.location, data.len() as i32, &data[0] as *const u32);
+			});
+		}
+		Uniform::Vector2Array(data) => {
+			if data.len() == 0 {
+				return;
+			}
+			gl_call!({
+				gl::Uniform2fv(loc.location, data.len() as i32, &data[0].x as *const f32);
+			});
+		}
+		Uniform::Vector3Array(data) => {
+			if data.len() == 0 {
+				return;
+			}
+			gl_call!({
+				gl::Uniform3fv(loc.location, data.len() as i32, &data[0].x as *const f32);
+			});
+		}
+		Uniform::Vector4Array(data) => {
+			if data.len() == 0 {
+				return;
+			}
+			gl_call!({
+				gl::Uniform4fv(loc.location, data.len() as i32, &data[0].x as *const f32);
+			});
+		}
+		Uniform::Matrix3Array(data) => {
+			if data.len() == 0 {
+				return;
+			}
+			gl_call!({
+				gl::UniformMatrix3fv(
+					loc.location,
+					data.len() as i32,
+					gl::FALSE,
+					&data[0].elements[0] as *const f32,
+				);
+			});
+		}
+		Uniform::Matrix4Array(data) => {
+			if data.len() == 0 {
+				return;
+			}
+			gl_call!({
+				gl::UniformMatrix4fv(
+					loc.location,
+					data.len() as i32,
+					gl::FALSE,
+					&data[0].elements[0] as *const f32,
+				);
+			});
+		}
+		// expanded to members in set_uniforms
+		Uniform::Struct(_) | Uniform::StructArray(_) => {}
 		Uniform::Texture2D(mut data, _) => {
 			gl_call!({
 				gl::ActiveTexture(gl::TEXTURE0 + loc.texture_slot as u32);
@@ -125,6 +237,17 @@ pub fn set_uniform(uniform: Uniform, loc: &UniformLocation, texture_store: &mut
 	};
 }
 
+fn has_texture(uniform: &Uniform) -> bool {
+	match uniform {
+		Uniform::Texture2D(_, _) => true,
+		Uniform::Struct(members) => members.iter().any(|(_, uniform)| has_texture(uniform)),
+		Uniform::StructArray(items) => items
+			.iter()
+			.any(|members| members.iter().any(|(_, uniform)| has_texture(uniform))),
+		_ => false,
+	}
+}
+
 pub fn set_uniforms(
 	uniforms: &HashMap<UniformName, Uniform>,
 	shader_program: &mut ShaderProgram,
@@ -134,72 +257,125 @@ pub fn set_uniforms(
 ) {
 	uniforms.iter().for_each(|(name, uniform)| {
 		// TODO: do not rebind textures if material is not changed
-		let is_texture = match uniform {
-			Uniform::Texture2D(_, _) => true,
-			_ => false,
-		};
+		let is_texture = has_texture(uniform);
 
 		if !force && !shader_program.set_uniform(name, (*uniform).clone()) && !is_texture {
 			return;
 		}
 
-		if shader_program_id.uniform_locations.get(name).is_none() {
-			let c_name = CString::new(name.get_name().as_bytes()).unwrap();
-			let location;
+		apply_uniform(name, uniform, shader_program_id, texture_store);
+	});
+}
 
-			gl_call!({
-				location = gl::GetUniformLocation(shader_program_id.id, c_name.as_ptr());
+fn apply_uniform(
+	name: &UniformName,
+	uniform: &Uniform,
+	shader_program_id: &mut GLShaderProgramID,
+	texture_store: &mut GLTextureIDs,
+) {
+	match uniform {
+		Uniform::Struct(members) => {
+			members.iter().for_each(|(member, uniform)| {
+				let name = UniformName::Other(format!("{}.{}", name.get_name(), member));
+				apply_uniform(&name, uniform, shader_program_id, texture_store);
 			});
-
-			println!(">>...........{:?} {}", name, location);
-			if let Uniform::Texture2D(_, _) = uniform {
-				println!("...........{} {:?}", shader_program_id.texture_slots, name);
-				gl_call!({
-					gl::Uniform1i(location, shader_program_id.texture_slots);
+			return;
+		}
+		Uniform::StructArray(items) => {
+			items.iter().enumerate().for_each(|(i, members)| {
+				members.iter().for_each(|(member, uniform)| {
+					let name = UniformName::Other(format!("{}[{}].{}", name.get_name(), i, member));
+					apply_uniform(&name, uniform, shader_program_id, texture_store);
 				});
-				shader_program_id.uniform_locations.insert(
-					name.clone(),
-					UniformLocation {
-						location,
-						texture_slot: shader_program_id.texture_slots,
-					},
-				);
-				shader_program_id.texture_slots += 1;
-			} else {
-				shader_program_id.uniform_locations.insert(
-					name.clone(),
-					UniformLocation {
-						location,
-						texture_slot: -1,
-					},
-				);
-			}
+			});
+			return;
 		}
+		_ => {}
+	}
 
-		if let Some(uniform_location) = shader_program_id.uniform_locations.get(name) {
-			set_uniform(uniform.clone(), uniform_location, texture_store);
+	if shader_program_id.uniform_locations.get(name).is_none() {
+		let c_name = CString::new(name.get_name().as_bytes()).unwrap();
+		let location;
+
+		gl_call!({
+			location = gl::GetUniformLocation(shader_program_id.id, c_name.as_ptr());
+		});
+
+		println!(">>...........{:?} {}", name, location);
+		if let Uniform::Texture2D(_, _) = uniform {
+			println!("...........{} {:?}", shader_program_id.texture_slots, name);
+			gl_call!({
+				gl::Uniform1i(location, shader_program_id.texture_slots);
+			});
+			shader_program_id.uniform_locations.insert(
+				name.clone(),
+				UniformLocation {
+					location,
+					texture_slot: shader_program_id.texture_slots,
+				},
+			);
+			shader_program_id.texture_slots += 1;
+		} else {
+			shader_program_id.uniform_locations.insert(
+				name.clone(),
+				UniformLocation {
+					location,
+					texture_slot: -1,
+				},
+			);
 		}
-	});
+	}
+
+	if let Some(uniform_location) = shader_program_id.uniform_locations.get(name) {
+		set_uniform(uniform.clone(), uniform_location, texture_store);
+	}
 }
 
-pub fn read_shader_file(bind_context: &BindContext, path: &str) -> String {
-	let path = path.to_string() + ".glsl";
+pub fn read_shader_file(path: &str) -> String {
+	match read_include(SHADER_DIRS, path) {
+		Ok(code) => code,
+		Err(err) => {
+			println!("ERROR::SHADER::READ_FAILED: {}", err);
+			panic!();
+		}
+	}
+}
 
-	let p = find_file(&["res/shaders"], &path).unwrap();
-	let mut code = read_to_string(&p);
+pub fn preprocess_shader(code: &str, src_path: &str) -> String {
+	let mut preprocessor = ShaderPreprocessor::new(SHADER_DIRS);
+	match preprocessor.process(src_path, code) {
+		Ok(code) => code,
+		Err(err) => {
+			println!("ERROR::SHADER::PREPROCESS_FAILED: {}\n{}", src_path, err);
+			panic!();
+		}
+	}
+}
 
-	// let mut code = bind_context.shader_sources
-	// 	.iter()
-	// 	.find(|e| e.name == path)
-	// 	.unwrap()
-	// 	.src.to_string();
+// instance attribute definitions carry their locations
+fn instance_definitions<'a>(bind_context: &'a BindContext) -> impl Iterator<Item = String> + 'a {
+	bind_context
+		.instance_definitions
+		.iter()
+		.map(|(name, location)| format!("#define {} {}\n", name, location))
+}
 
-	while let Some(cap) = RE_INCLUDE.captures(&code.clone()) {
-		let include_data = read_shader_file(bind_context, &cap[1]);
-		code = code.replace(&cap[0], &include_data);
+// a leading #version of the source is kept in front of the definitions, 330 core otherwise
+fn split_version(code: &str) -> (&str, &str) {
+	let body = code.trim_start();
+	if body.starts_with("#version") {
+		let end = body.find('\n').unwrap_or(body.len());
+		return (&body[..end], &body[end..]);
 	}
+	("#version 330 core", code)
+}
 
-	code
+fn get_custom_definitions(shader: &ShaderProgram) -> String {
+	shader
+		.get_defines()
+		.iter()
+		.map(|(name, value)| format!("#define {} {}\n", name, value))
+		.collect()
 }
 
 fn set_definitions_fragment(code: &String, shader: &ShaderProgram, bind_context: &mut BindContext) -> String {
@@ -236,14 +412,21 @@ fn set_definitions_fragment(code: &String, shader: &ShaderProgram, bind_context:
 				.iter()
 				.map(|attribute| format!("#define {}\n", attribute.definition())),
 		)
+		.chain(instance_definitions(bind_context))
 		.collect();
 
 	// println!("<><><><<><><><>><><<><><\n{}", core_definitions);
 	// println!("..................\n{}..................\n", textures);
 
+	let (version, code) = split_version(code);
 	format!(
-		"#version 330 core\n{}\n{}\n{}\n{}",
-		core_definitions, definitions, textures, code
+		"{}\n{}\n{}\n{}\n{}\n{}",
+		version,
+		core_definitions,
+		definitions,
+		textures,
+		get_custom_definitions(shader),
+		code
 	)
 }
 
@@ -273,21 +456,24 @@ fn set_definitions_vertex(code: &String, shader: &ShaderProgram, bind_context: &
 				.iter()
 				.map(|attribute| format!("#define {}\n", attribute.definition())),
 		)
+		.chain(instance_definitions(bind_context))
 		.collect();
 
-	format!("#version 330 core\n{}\n{}\n{}", definitions, textures, code)
+	let (version, code) = split_version(code);
+	format!(
+		"{}\n{}\n{}\n{}\n{}",
+		version,
+		definitions,
+		textures,
+		get_custom_definitions(shader),
+		code
+	)
 }
 
-pub fn get_program(shader: &ShaderProgram, bind_context: &mut BindContext) -> GLShaderProgramID {
-	let code = read_shader_file(bind_context, shader.get_src());
-
-	let mut shader_program = GLShaderProgramID {
-		fs_source: String::from(""),
-		vs_source: String::from(""),
-		id: 0,
-		uniform_locations: HashMap::new(),
-		texture_slots: 0,
-	};
+fn split_program(code: &str) -> (String, String, Option<String>) {
+	let mut vs_source = String::new();
+	let mut fs_source = String::new();
+	let mut gs_source: Option<String> = None;
 
 	let mut write_to_prog = ProgramType::None;
 
@@ -296,24 +482,55 @@ pub fn get_program(shader: &ShaderProgram, bind_context: &mut BindContext) -> GL
 			write_to_prog = ProgramType::Vertex;
 		} else if line.starts_with("#<fragment>") {
 			write_to_prog = ProgramType::Fragment;
+		} else if line.starts_with("#<geometry>") {
+			write_to_prog = ProgramType::Geometry;
+			gs_source.get_or_insert(String::new());
 		} else {
-			match write_to_prog {
-				ProgramType::Vertex => {
-					shader_program.vs_source += line;
-					shader_program.vs_source += "\n";
-				}
-				ProgramType::Fragment => {
-					shader_program.fs_source += line;
-					shader_program.fs_source += "\n";
-				}
-				_ => {}
-			}
+			let target = match write_to_prog {
+				ProgramType::Vertex => &mut vs_source,
+				ProgramType::Fragment => &mut fs_source,
+				ProgramType::Geometry => gs_source.as_mut().unwrap(),
+				ProgramType::None => continue,
+			};
+			*target += line;
+			*target += "\n";
 		}
 	}
 
+	(vs_source, fs_source, gs_source)
+}
+
+pub fn get_program(shader: &ShaderProgram, bind_context: &mut BindContext) -> GLShaderProgramID {
+	let (vs_source, fs_source, gs_source) = match shader.get_source() {
+		ShaderSource::File(path) => split_program(&read_shader_file(path)),
+		ShaderSource::Inline {
+			vertex,
+			fragment,
+			geometry,
+		} => (vertex.clone(), fragment.clone(), geometry.clone()),
+	};
+
+	let mut shader_program = GLShaderProgramID {
+		fs_source,
+		vs_source,
+		gs_source,
+		id: 0,
+		uniform_locations: HashMap::new(),
+		texture_slots: 0,
+		reflection: ShaderReflection::default(),
+	};
+
 	shader_program.fs_source = set_definitions_fragment(&shader_program.fs_source, shader, bind_context);
 	shader_program.vs_source = set_definitions_vertex(&shader_program.vs_source, shader, bind_context);
 
+	shader_program.fs_source = preprocess_shader(&shader_program.fs_source, shader.get_src());
+	shader_program.vs_source = preprocess_shader(&shader_program.vs_source, shader.get_src());
+
+	shader_program.gs_source = shader_program.gs_source.take().map(|gs_source| {
+		let gs_source = set_definitions_vertex(&gs_source, shader, bind_context);
+		preprocess_shader(&gs_source, shader.get_src())
+	});
+
 	println!("=============================================");
 	println!("{}", shader_program.vs_source);
 	println!(">>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>");
@@ -336,9 +553,16 @@ pub fn compile_shader_program(shader: &ShaderProgram, bind_context: &mut BindCon
 
 		let vs = compile_shader(gl::VERTEX_SHADER, &program.vs_source[..], shader.get_src());
 		let fs = compile_shader(gl::FRAGMENT_SHADER, &program.fs_source[..], shader.get_src());
+		let gs = program
+			.gs_source
+			.as_ref()
+			.map(|gs_source| compile_shader(gl::GEOMETRY_SHADER, &gs_source[..], shader.get_src()));
 
 		gl::AttachShader(id, fs);
 		gl::AttachShader(id, vs);
+		if let Some(gs) = gs {
+			gl::AttachShader(id, gs);
+		}
 
 		gl::LinkProgram(id);
 		gl::ValidateProgram(id);
@@ -358,8 +582,14 @@ pub fn compile_shader_program(shader: &ShaderProgram, bind_context: &mut BindCon
 		// TODO - release remove shaders
 		gl::DeleteShader(vs);
 		gl::DeleteShader(fs);
+		if let Some(gs) = gs {
+			gl::DeleteShader(gs);
+		}
 	});
 
+	bind_uniform_blocks(program.id);
+	program.reflection = reflect_program(program.id);
+
 	gl_call!({
 		gl::UseProgram(program.id);
 	});
@@ -369,6 +599,118 @@ pub fn compile_shader_program(shader: &ShaderProgram, bind_context: &mut BindCon
 	program
 }
 
+pub fn reflect_program(program_id: GLuint) -> ShaderReflection {
+	let mut reflection = ShaderReflection::default();
+	let mut count = 0;
+	let mut max_len = 0;
+
+	gl_call!({
+		gl::GetProgramiv(program_id, gl::ACTIVE_UNIFORMS, &mut count);
+		gl::GetProgramiv(program_id, gl::ACTIVE_UNIFORM_MAX_LENGTH, &mut max_len);
+	});
+
+	for i in 0..count as GLuint {
+		let (name, size, data_type) = get_active(program_id, i, max_len, gl::GetActiveUniform);
+
+		// members of uniform blocks are filled from uniform buffers
+		let mut block_index = -1;
+		gl_call!({
+			gl::GetActiveUniformsiv(program_id, 1, &i, gl::UNIFORM_BLOCK_INDEX, &mut block_index);
+		});
+		if block_index != -1 {
+			continue;
+		}
+
+		let c_name = CString::new(name.as_bytes()).unwrap();
+		let location;
+		gl_call!({
+			location = gl::GetUniformLocation(program_id, c_name.as_ptr());
+		});
+
+		reflection.uniforms.push(ActiveUniform {
+			name,
+			location,
+			size,
+			data_type: to_shader_data_type(data_type),
+		});
+	}
+
+	gl_call!({
+		gl::GetProgramiv(program_id, gl::ACTIVE_ATTRIBUTES, &mut count);
+		gl::GetProgramiv(program_id, gl::ACTIVE_ATTRIBUTE_MAX_LENGTH, &mut max_len);
+	});
+
+	for i in 0..count as GLuint {
+		let (name, size, data_type) = get_active(program_id, i, max_len, gl::GetActiveAttrib);
+		if name.starts_with("gl_") {
+			continue;
+		}
+
+		let c_name = CString::new(name.as_bytes()).unwrap();
+		let location;
+		gl_call!({
+			location = gl::GetAttribLocation(program_id, c_name.as_ptr());
+		});
+
+		reflection.attributes.push(ActiveAttribute {
+			name,
+			location,
+			size,
+			data_type: to_shader_data_type(data_type),
+		});
+	}
+
+	reflection.attributes.sort_by_key(|e| e.location);
+	reflection
+}
+
+type GetActiveFn = unsafe fn(GLuint, GLuint, GLsizei, *mut GLsizei, *mut GLint, *mut GLenum, *mut GLchar);
+
+fn get_active(program_id: GLuint, index: GLuint, max_len: GLint, get: GetActiveFn) -> (String, i32, GLenum) {
+	let mut buffer = vec![0u8; max_len.max(1) as usize];
+	let mut length = 0;
+	let mut size = 0;
+	let mut data_type = 0;
+
+	gl_call!({
+		get(
+			program_id,
+			index,
+			buffer.len() as GLsizei,
+			&mut length,
+			&mut size,
+			&mut data_type,
+			buffer.as_mut_ptr() as *mut GLchar,
+		);
+	});
+
+	buffer.truncate(length as usize);
+	(String::from_utf8_lossy(&buffer).to_string(), size, data_type)
+}
+
+fn to_shader_data_type(data_type: GLenum) -> ShaderDataType {
+	match data_type {
+		gl::FLOAT => ShaderDataType::Float,
+		gl::FLOAT_VEC2 => ShaderDataType::Vec2,
+		gl::FLOAT_VEC3 => ShaderDataType::Vec3,
+		gl::FLOAT_VEC4 => ShaderDataType::Vec4,
+		gl::INT => ShaderDataType::Int,
+		gl::INT_VEC2 => ShaderDataType::IVec2,
+		gl::INT_VEC3 => ShaderDataType::IVec3,
+		gl::INT_VEC4 => ShaderDataType::IVec4,
+		gl::UNSIGNED_INT => ShaderDataType::UInt,
+		gl::UNSIGNED_INT_VEC2 => ShaderDataType::UVec2,
+		gl::UNSIGNED_INT_VEC3 => ShaderDataType::UVec3,
+		gl::UNSIGNED_INT_VEC4 => ShaderDataType::UVec4,
+		gl::BOOL => ShaderDataType::Bool,
+		gl::FLOAT_MAT2 => ShaderDataType::Mat2,
+		gl::FLOAT_MAT3 => ShaderDataType::Mat3,
+		gl::FLOAT_MAT4 => ShaderDataType::Mat4,
+		gl::SAMPLER_2D => ShaderDataType::Sampler2D,
+		_ => ShaderDataType::Other(data_type),
+	}
+}
+
 pub fn compile_shader(t: GLenum, src: &str, src_path: &str) -> u32 {
 	let id;
 
@@ -397,6 +739,11 @@ pub fn compile_shader(t: GLenum, src: &str, src_path: &str) -> u32 {
 					src_path,
 					info_log.to_string_lossy()
 				),
+				gl::GEOMETRY_SHADER => println!(
+					"ERROR::SHADER::GEOMETRY::COMPILATION_FAILED: {}\n{}",
+					src_path,
+					info_log.to_string_lossy()
+				),
 				_ => println!(
 					"ERROR::SHADER::?::COMPILATION_FAILED: {}\n{}",
 					src_path,
@@ -3,12 +3,19 @@ extern crate uuid;
 
 use self::gl::types::*;
 use self::uuid::Uuid;
-use core::{MagFilter, MinFilter, Texture2D, TextureColorType, TextureDataSource, Wrapping};
+use core::{mip_size, CompressedFormat, MagFilter, MinFilter, Texture2D, TextureColorType, TextureDataSource, Wrapping};
 use std::collections::HashMap;
 use std::os::raw::c_void;
 
 pub type GLTextureIDs = HashMap<Uuid, TextureId>;
 
+// EXT_texture_compression_s3tc and KHR_texture_compression_astc_ldr are not in the core bindings
+const COMPRESSED_RGB_S3TC_DXT1_EXT: u32 = 0x83F0;
+const COMPRESSED_RGBA_S3TC_DXT5_EXT: u32 = 0x83F3;
+const COMPRESSED_RGBA_ASTC_4X4_KHR: u32 = 0x93B0;
+const COMPRESSED_RGBA_ASTC_6X6_KHR: u32 = 0x93B4;
+const COMPRESSED_RGBA_ASTC_8X8_KHR: u32 = 0x93B7;
+
 #[derive(Debug)]
 pub struct TextureId {
 	pub id: GLuint,
@@ -35,6 +42,13 @@ impl GLTexture for Texture2D {
 		let gl_texture_dimensions = gl::TEXTURE_2D;
 		// let gl_texture_dimensions = get_texture_dimensions(&self.dimensions);
 
+		if self.pending {
+			gl_call!({
+				gl::BindTexture(gl_texture_dimensions, 0);
+			});
+			return 0;
+		}
+
 		if self.need_update {
 			hash_map.remove(&self.uuid);
 			self.need_update = false;
@@ -121,6 +135,48 @@ pub fn load_texture(texture: &mut Texture2D) -> Result<TextureId, ()> {
 				);
 			});
 		}
+		// baked levels replace runtime mip generation
+		TextureDataSource::Mipmaps(levels) => {
+			for (level, data) in levels.iter().enumerate() {
+				gl_call!({
+					gl::TexImage2D(
+						gl_texture_dimensions,
+						level as i32,
+						to_gl_color_internal_type(texture_data.color_type) as i32,
+						mip_size(texture_data.width, level) as i32,
+						mip_size(texture_data.height, level) as i32,
+						0,
+						to_gl_color_pixel_data_type(texture_data.color_type),
+						gl::UNSIGNED_BYTE,
+						&data[0] as *const u8 as *const c_void,
+					);
+				});
+			}
+			gl_call!({
+				gl::TexParameteri(gl_texture_dimensions, gl::TEXTURE_MAX_LEVEL, levels.len() as i32 - 1);
+			});
+			uploaded = true;
+		}
+		TextureDataSource::Compressed(format, levels) => {
+			for (level, data) in levels.iter().enumerate() {
+				gl_call!({
+					gl::CompressedTexImage2D(
+						gl_texture_dimensions,
+						level as i32,
+						to_gl_compressed_format(*format),
+						mip_size(texture_data.width, level) as i32,
+						mip_size(texture_data.height, level) as i32,
+						0,
+						data.len() as i32,
+						&data[0] as *const u8 as *const c_void,
+					);
+				});
+			}
+			gl_call!({
+				gl::TexParameteri(gl_texture_dimensions, gl::TEXTURE_MAX_LEVEL, levels.len() as i32 - 1);
+			});
+			uploaded = true;
+		}
 		TextureDataSource::RawUploaded => panic!("can't upload to video memory cleaned texture"),
 		TextureDataSource::TextureBuffer => {
 			gl_call!({
@@ -191,6 +247,19 @@ pub fn to_gl_color_internal_type(color_type: TextureColorType) -> u32 {
 	}
 }
 
+pub fn to_gl_compressed_format(format: CompressedFormat) -> u32 {
+	match format {
+		CompressedFormat::Bc1 => COMPRESSED_RGB_S3TC_DXT1_EXT,
+		CompressedFormat::Bc3 => COMPRESSED_RGBA_S3TC_DXT5_EXT,
+		CompressedFormat::Bc7 => gl::COMPRESSED_RGBA_BPTC_UNORM,
+		CompressedFormat::Etc2Rgb => gl::COMPRESSED_RGB8_ETC2,
+		CompressedFormat::Etc2Rgba => gl::COMPRESSED_RGBA8_ETC2_EAC,
+		CompressedFormat::Astc4x4 => COMPRESSED_RGBA_ASTC_4X4_KHR,
+		CompressedFormat::Astc6x6 => COMPRESSED_RGBA_ASTC_6X6_KHR,
+		CompressedFormat::Astc8x8 => COMPRESSED_RGBA_ASTC_8X8_KHR,
+	}
+}
+
 pub fn to_gl_color_pixel_data_type(color_type: TextureColorType) -> u32 {
 	match color_type {
 		TextureColorType::R(_) => gl::RED,
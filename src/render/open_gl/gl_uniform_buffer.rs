@@ -0,0 +1,216 @@
+extern crate byteorder;
+extern crate gl;
+
+use self::byteorder::{LittleEndian, WriteBytesExt};
+use self::gl::types::*;
+use core::{DirectionalLight, PointLight};
+use math::{Matrix4, Vector, Vector3};
+use std::ffi::CString;
+use std::os::raw::c_void;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UniformBlock {
+	Camera,
+	PointLights,
+	DirectionalLights,
+}
+
+impl UniformBlock {
+	pub fn all() -> [UniformBlock; 3] {
+		[
+			UniformBlock::Camera,
+			UniformBlock::PointLights,
+			UniformBlock::DirectionalLights,
+		]
+	}
+
+	pub fn get_name(&self) -> &str {
+		match self {
+			UniformBlock::Camera => "Camera",
+			UniformBlock::PointLights => "PointLights",
+			UniformBlock::DirectionalLights => "DirectionalLights",
+		}
+	}
+
+	pub fn binding(&self) -> GLuint {
+		match self {
+			UniformBlock::Camera => 0,
+			UniformBlock::PointLights => 1,
+			UniformBlock::DirectionalLights => 2,
+		}
+	}
+}
+
+#[derive(Debug)]
+pub struct UniformBuffer {
+	id: GLuint,
+	block: UniformBlock,
+	byte_size: usize,
+	data: Vec<u8>,
+}
+
+impl Drop for UniformBuffer {
+	fn drop(&mut self) {
+		gl_call!({
+			gl::DeleteBuffers(1, &self.id);
+		});
+	}
+}
+
+impl UniformBuffer {
+	pub fn new(block: UniformBlock) -> Self {
+		let mut id = 0;
+		gl_call!({
+			gl::GenBuffers(1, &mut id);
+		});
+
+		Self {
+			id,
+			block,
+			byte_size: 0,
+			data: Vec::new(),
+		}
+	}
+
+	pub fn set_data(&mut self, data: Vec<u8>) {
+		if data.len() == 0 || data == self.data {
+			return;
+		}
+
+		gl_call!({
+			gl::BindBuffer(gl::UNIFORM_BUFFER, self.id);
+		});
+
+		if data.len() == self.byte_size {
+			gl_call!({
+				gl::BufferSubData(
+					gl::UNIFORM_BUFFER,
+					0,
+					data.len() as GLsizeiptr,
+					&data[0] as *const u8 as *const c_void,
+				);
+			});
+		} else {
+			gl_call!({
+				gl::BufferData(
+					gl::UNIFORM_BUFFER,
+					data.len() as GLsizeiptr,
+					&data[0] as *const u8 as *const c_void,
+					gl::DYNAMIC_DRAW,
+				);
+			});
+			self.byte_size = data.len();
+		}
+
+		gl_call!({
+			gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+		});
+		self.data = data;
+	}
+
+	pub fn bind(&self) {
+		if self.byte_size == 0 {
+			return;
+		}
+
+		gl_call!({
+			gl::BindBufferBase(gl::UNIFORM_BUFFER, self.block.binding(), self.id);
+		});
+	}
+}
+
+// Per frame data shared by all programs, layout matches `snippet-camera` and `snippet-lights`
+#[derive(Debug)]
+pub struct UniformBuffers {
+	camera: UniformBuffer,
+	point_lights: UniformBuffer,
+	directional_lights: UniformBuffer,
+}
+
+impl UniformBuffers {
+	pub fn new() -> Self {
+		Self {
+			camera: UniformBuffer::new(UniformBlock::Camera),
+			point_lights: UniformBuffer::new(UniformBlock::PointLights),
+			directional_lights: UniformBuffer::new(UniformBlock::DirectionalLights),
+		}
+	}
+
+	pub fn set_camera(&mut self, matrix_view: &Matrix4<f32>, time: f32) {
+		let mut data = Vec::with_capacity(80);
+		write_matrix4(&mut data, matrix_view);
+		data.write_f32::<LittleEndian>(time).unwrap();
+		align(&mut data, 16);
+
+		self.camera.set_data(data);
+	}
+
+	pub fn set_point_lights(&mut self, lights: &[(&PointLight, Vector3<f32>)]) {
+		let mut data = Vec::with_capacity(lights.len() * 48);
+		lights.iter().for_each(|(light, position)| {
+			let mut color = light.color.clone();
+			color.multiply_scalar(light.power);
+
+			write_vector3(&mut data, position);
+			align(&mut data, 16);
+			write_vector3(&mut data, &color);
+			data.write_f32::<LittleEndian>(light.distance).unwrap();
+			data.write_f32::<LittleEndian>(light.decay).unwrap();
+			align(&mut data, 16);
+		});
+
+		self.point_lights.set_data(data);
+	}
+
+	pub fn set_directional_lights(&mut self, lights: &[(&DirectionalLight, Vector3<f32>)]) {
+		let mut data = Vec::with_capacity(lights.len() * 32);
+		lights.iter().for_each(|(light, direction)| {
+			let mut color = light.color.clone();
+			color.multiply_scalar(light.power);
+
+			write_vector3(&mut data, direction);
+			align(&mut data, 16);
+			write_vector3(&mut data, &color);
+			align(&mut data, 16);
+		});
+
+		self.directional_lights.set_data(data);
+	}
+
+	pub fn bind(&self) {
+		self.camera.bind();
+		self.point_lights.bind();
+		self.directional_lights.bind();
+	}
+}
+
+pub fn bind_uniform_blocks(program_id: GLuint) {
+	UniformBlock::all().iter().for_each(|block| {
+		let c_name = CString::new(block.get_name().as_bytes()).unwrap();
+
+		gl_call!({
+			let index = gl::GetUniformBlockIndex(program_id, c_name.as_ptr());
+			if index != gl::INVALID_INDEX {
+				gl::UniformBlockBinding(program_id, index, block.binding());
+			}
+		});
+	});
+}
+
+fn write_vector3(data: &mut Vec<u8>, v: &Vector3<f32>) {
+	data.write_f32::<LittleEndian>(v.x).unwrap();
+	data.write_f32::<LittleEndian>(v.y).unwrap();
+	data.write_f32::<LittleEndian>(v.z).unwrap();
+}
+
+fn write_matrix4(data: &mut Vec<u8>, m: &Matrix4<f32>) {
+	m.elements.iter().for_each(|e| {
+		data.write_f32::<LittleEndian>(*e).unwrap();
+	});
+}
+
+fn align(data: &mut Vec<u8>, alignment: usize) {
+	while data.len() % alignment != 0 {
+		data.push(0);
+	}
+}
@@ -8,10 +8,13 @@ extern crate uuid;
 
 #[macro_use] pub mod macros;
 mod gl_geometry;
+mod gl_instances;
 mod gl_material;
 pub mod systems;
 mod gl_texture;
 mod gl_shader_program;
+mod gl_shader_preprocessor;
+mod gl_uniform_buffer;
 mod gl_render_buffer;
 mod gl_frame_buffer;
 
@@ -20,6 +23,7 @@ extern crate image;
 pub use self::systems::*;
 use self::systems::system_render::*;
 use self::gl_geometry::GLGeometry;
+use self::gl_instances::GLInstances;
 use self::gl_material::GLMaterial;
 
 
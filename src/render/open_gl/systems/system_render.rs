@@ -5,13 +5,15 @@ extern crate rayon;
 extern crate specs;
 extern crate uuid;
 
+use std::collections::{HashMap, HashSet};
 use std::ffi::CStr;
+use std::mem;
 use std::os::raw::c_void;
 use std::time::{Duration, Instant};
 
 use core::{
-	Blending, BufferGeometry, BufferGroup, DirectionalLight, EntityRelations, FrameBuffer, Material, PerspectiveCamera,
-	PointLight, ShaderTag, SharedFrameBuffer, SharedGeometry, SharedMaterials, Transform, TransformLock, UniformName,
+	Blending, BufferGeometry, BufferGroup, DirectionalLight, EntityRelations, FrameBuffer, Instances, Lod, Material,
+	PerspectiveCamera, PointLight, ShaderTag, SharedFrameBuffer, SharedGeometry, SharedMaterials, Transform, TransformLock, UniformName,
 };
 
 use self::gl::types::*;
@@ -19,14 +21,17 @@ use self::gl::GetString;
 use self::glutin::dpi::*;
 use self::glutin::{ContextError, ContextWrapper, EventsLoop, Window};
 use self::specs::prelude::*;
+use self::uuid::Uuid;
 
 use super::super::{
 	gl_frame_buffer::{GLFrameBuffer, GLFrameBufferIDs},
-	gl_geometry::VertexArraysIDs,
+	gl_geometry::{to_gl_draw_mode, VertexArraysIDs},
+	gl_instances::GLInstanceIDs,
 	gl_material::GLMaterialIDs,
 	gl_render_buffer::GLRenderBufferIDs,
 	gl_texture::GLTextureIDs,
-	GLGeometry, GLMaterial,
+	gl_uniform_buffer::UniformBuffers,
+	GLGeometry, GLInstances, GLMaterial,
 };
 use math::{Matrix3, Matrix4, Vector, Vector3, Vector4};
 use std::sync::{Arc, Mutex};
@@ -39,16 +44,17 @@ pub struct BindContext<'z, 'x> {
 	pub lights_point_count: usize,
 	pub lights_directional_count: usize,
 	pub geometry: &'x BufferGeometry,
+	pub instance_definitions: &'x [(String, usize)],
+	pub strict: bool,
 }
 
 struct DrawGroup {
 	buffer_group: BufferGroup,
 	matrix_model: Matrix4<f32>,
-	matrix_projection: Matrix4<f32>,
 	matrix_normal: Matrix3<f32>,
 	material: Arc<Mutex<Material>>,
 	geometry: SharedGeometry,
-	time: f32,
+	instances: Option<(Uuid, Vec<(String, usize)>)>,
 	distance: f32,
 }
 
@@ -64,16 +70,24 @@ pub struct RenderSystem {
 	pub clear_color_need_update: bool,
 	pub tags: Vec<ShaderTag>,
 	pub override_material: Option<SharedMaterials>,
+	// report material uniforms and geometry attributes that don't match compiled programs
+	pub strict_shaders: bool,
+	// draw everything as wireframe, materials can also enable it on their own
+	pub wireframe: bool,
 
 	lights_point_count: usize,
 	lights_directional_count: usize,
 	render_queue: Vec<DrawGroup>,
+	// versions of invalid instances that were already reported
+	invalid_instances: HashMap<Uuid, usize>,
+	uniform_buffers: UniformBuffers,
 
 	depth_test: bool,
 	stencil_test: bool,
 	blending: bool,
 
 	blending_state: Blending,
+	wireframe_state: bool,
 
 	frame_buffer: Option<SharedFrameBuffer>,
 	current_frame_buffer: Option<SharedFrameBuffer>,
@@ -83,6 +97,7 @@ impl RenderSystem {
 	pub fn new(world: &mut World, depth_test: bool, stencil_test: bool, blending: bool) -> Self {
 		// TODO: ensure once
 		world.insert(VertexArraysIDs::new());
+		world.insert(GLInstanceIDs::new());
 		world.insert(GLMaterialIDs::new());
 		world.insert(GLTextureIDs::new());
 		world.insert(GLRenderBufferIDs::new());
@@ -134,16 +149,21 @@ impl RenderSystem {
 			clear_color_need_update: true,
 			tags: Vec::new(),
 			override_material: None,
+			strict_shaders: false,
+			wireframe: false,
 			// render_settings: RenderSettings::default(),
 			lights_point_count: 0,
 			lights_directional_count: 0,
 			render_queue: vec![],
+			invalid_instances: HashMap::new(),
+			uniform_buffers: UniformBuffers::new(),
 
 			depth_test,
 			stencil_test,
 			blending,
 
 			blending_state: Blending::None,
+			wireframe_state: false,
 
 			frame_buffer: None,
 			current_frame_buffer: None,
@@ -209,19 +229,34 @@ impl RenderSystem {
 		self.blending_state = blending;
 	}
 
+	fn set_wireframe_mode(&mut self, wireframe: bool) {
+		if self.wireframe_state == wireframe {
+			return;
+		}
+
+		gl_call!({
+			gl::PolygonMode(gl::FRONT_AND_BACK, if wireframe { gl::LINE } else { gl::FILL });
+		});
+
+		self.wireframe_state = wireframe;
+	}
+
 	fn draw_buffer_group<'x, 'z>(
 		&mut self,
 		mut groupe: DrawGroup,
 		mut gl_material_ids: &'z mut GLMaterialIDs,
 		mut gl_texture_ids: &'z mut GLTextureIDs,
 		mut vertex_arrays_ids: &'z mut VertexArraysIDs,
+		gl_instance_ids: &'z mut GLInstanceIDs,
 	) {
-		{
+		let draw_mode = {
 			let geometry = &groupe.geometry.lock().unwrap();
 			geometry.bind(&mut vertex_arrays_ids);
 
 			let material = &mut groupe.material.lock().unwrap();
 			self.set_blending_mode(material.blending);
+			let wireframe = self.wireframe || material.wireframe;
+			self.set_wireframe_mode(wireframe);
 
 			let mut bind_context = BindContext {
 				gl_texture_ids: &mut gl_texture_ids,
@@ -230,23 +265,31 @@ impl RenderSystem {
 				lights_point_count: self.lights_point_count,
 				lights_directional_count: self.lights_directional_count,
 				geometry,
+				instance_definitions: groupe.instances.as_ref().map_or(&[], |(_, definitions)| &definitions[..]),
+				strict: self.strict_shaders,
 			};
 
 			material.set_uniform(UniformName::MatrixModel, groupe.matrix_model);
-			material.set_uniform(UniformName::MatrixView, groupe.matrix_projection);
 			material.set_uniform(UniformName::MatrixNormal, groupe.matrix_normal);
-			material.set_uniform(UniformName::Time, groupe.time);
 			material.bind(&mut bind_context);
-		}
-
-		let geometry = &mut groupe.geometry.lock().unwrap();
+			to_gl_draw_mode(geometry.draw_mode)
+		};
 
 		let len = groupe.buffer_group.count as GLint;
-		let start = (groupe.buffer_group.start * geometry.get_vertex_byte_size()) as *const c_void;
+		let start = (groupe.buffer_group.start * mem::size_of::<u32>()) as *const c_void;
 
-		gl_call!({
-			gl::DrawElements(gl::TRIANGLES, len, gl::UNSIGNED_INT, start);
-		});
+		match groupe.instances.as_ref().and_then(|(uuid, _)| gl_instance_ids.get(uuid)) {
+			Some(instance_buffer) => {
+				instance_buffer.bind();
+				gl_call!({
+					gl::DrawElementsInstanced(draw_mode, len, gl::UNSIGNED_INT, start, instance_buffer.count as GLsizei);
+				});
+				instance_buffer.unbind();
+			}
+			None => gl_call!({
+				gl::DrawElements(draw_mode, len, gl::UNSIGNED_INT, start);
+			}),
+		}
 	}
 
 	pub fn bind_frame_buffer(
@@ -285,11 +328,14 @@ impl RenderSystem {
 		let (
 			camera_coll,
 			transform_coll,
-			mut geometry_coll,
+			geometry_coll,
+			lod_coll,
+			instances_coll,
 			mut material_coll,
 			light_point_coll,
 			light_direct_coll,
 			mut vertex_arrays_ids,
+			mut gl_instance_ids,
 			mut gl_material_ids,
 			mut gl_texture_ids,
 			mut gl_frame_buffer_ids,
@@ -297,11 +343,14 @@ impl RenderSystem {
 		): (
 			ReadStorage<PerspectiveCamera>,
 			ReadStorage<Transform>,
-			WriteStorage<SharedGeometry>,
+			ReadStorage<SharedGeometry>,
+			ReadStorage<Lod>,
+			ReadStorage<Instances>,
 			WriteStorage<SharedMaterials>,
 			WriteStorage<PointLight>,
 			WriteStorage<DirectionalLight>,
 			Write<VertexArraysIDs>,
+			Write<GLInstanceIDs>,
 			Write<GLMaterialIDs>,
 			Write<GLTextureIDs>,
 			Write<GLFrameBufferIDs>,
@@ -385,52 +434,48 @@ impl RenderSystem {
 			light_materials_need_update = true;
 		}
 
+		self.uniform_buffers.set_camera(&matrix_projection, time);
+		self.uniform_buffers.set_point_lights(&lights_point);
+		self.uniform_buffers.set_directional_lights(&lights_direct);
+		self.uniform_buffers.bind();
+
 		entities.iter().for_each(|entity| {
 			// TODO: optimize
 			let mut override_material = self.override_material.clone();
 			let shared_materials = override_material.as_mut().or(material_coll.get_mut(*entity));
 			let transform = transform_coll.get(*entity);
-			let geometry = geometry_coll.get_mut(*entity);
+			let lod = lod_coll.get(*entity);
 
-			if transform.is_none() || geometry.is_none() || shared_materials.is_none() {
+			if transform.is_none() || shared_materials.is_none() {
 				return;
 			}
 
 			let transform = transform.unwrap();
-			let geometry = geometry.unwrap();
-			let shared_materials = shared_materials.unwrap();
-
-			shared_materials.iter_mut().for_each(|shared_material| {
-				let material = &mut shared_material.lock().unwrap();
 
-				if !material.has_tag(ShaderTag::Lighting) {
-					return;
+			let geometry = match lod {
+				Some(lod) => {
+					let mut pos = Vector3::zero();
+					(matrix_cam_position * transform.matrix_world * transform.matrix_local).get_position(&mut pos);
+					lod.get_level(pos.length()).or(geometry_coll.get(*entity))
 				}
+				None => geometry_coll.get(*entity),
+			};
 
-				lights_point.iter().enumerate().for_each(|(i, (light, pos))| {
-					let mut col = light.color.clone();
-					col.multiply_scalar(light.power);
+			if geometry.is_none() {
+				return;
+			}
 
-					material.set_uniform(UniformName::Other(format!("pointLights[{}].position", i)), pos.clone());
-					material.set_uniform(UniformName::Other(format!("pointLights[{}].color", i)), col);
-					material.set_uniform(UniformName::Other(format!("pointLights[{}].distance", i)), light.distance);
-					material.set_uniform(UniformName::Other(format!("pointLights[{}].decay", i)), light.decay);
-				});
-				lights_direct.iter().enumerate().for_each(|(i, (light, direction))| {
-					let mut col = light.color.clone();
-					col.multiply_scalar(light.power);
-
-					material.set_uniform(UniformName::Other(format!("directionalLights[{}].color", i)), col);
-					material.set_uniform(
-						UniformName::Other(format!("directionalLights[{}].direction", i)),
-						direction.clone(),
-					);
-				});
+			let mut geometry = geometry.unwrap().clone();
+			let shared_materials = shared_materials.unwrap();
 
-				if light_materials_need_update {
-					material.need_update();
-				}
-			});
+			if light_materials_need_update {
+				shared_materials.iter_mut().for_each(|shared_material| {
+					let material = &mut shared_material.lock().unwrap();
+					if material.has_tag(ShaderTag::Lighting) {
+						material.need_update();
+					}
+				});
+			}
 
 			let mut matrix_model = matrix_cam_position * transform.matrix_world * transform.matrix_local;
 
@@ -459,6 +504,21 @@ impl RenderSystem {
 			let mut matrix_normal = Matrix3::new();
 			matrix_normal.get_normal_matrix(&(matrix_cam_position * transform.matrix_world * transform.matrix_local));
 
+			let instances = match instances_coll.get(*entity) {
+				Some(instances) if instances.len() == 0 => return,
+				Some(instances) if !instances.is_valid() => {
+					if self.invalid_instances.insert(instances.uuid, instances.get_version()) != Some(instances.get_version()) {
+						println!("Instances: attributes don't match {} transforms, skipping draw", instances.len());
+					}
+					return;
+				}
+				Some(instances) => {
+					instances.update(&mut gl_instance_ids);
+					Some((instances.uuid, instances.definitions()))
+				}
+				None => None,
+			};
+
 			let mut groups = {
 				let geom = geometry.lock().unwrap();
 				if geom.groups.len() == 0 {
@@ -482,11 +542,10 @@ impl RenderSystem {
 				let mut groupe = DrawGroup {
 					buffer_group,
 					matrix_model,
-					matrix_projection,
 					matrix_normal,
 					material,
 					geometry: geometry.clone(),
-					time,
+					instances: instances.clone(),
 					distance: 0.0,
 				};
 
@@ -499,7 +558,7 @@ impl RenderSystem {
 					return;
 				}
 
-				self.draw_buffer_group(groupe, &mut gl_material_ids, &mut gl_texture_ids, &mut vertex_arrays_ids);
+				self.draw_buffer_group(groupe, &mut gl_material_ids, &mut gl_texture_ids, &mut vertex_arrays_ids, &mut gl_instance_ids);
 			});
 		});
 
@@ -509,11 +568,16 @@ impl RenderSystem {
 				let mut render_queue: Vec<DrawGroup> = self.render_queue.drain(..).collect();
 
 				render_queue.drain(..).for_each(|groupe| {
-					self.draw_buffer_group(groupe, &mut gl_material_ids, &mut gl_texture_ids, &mut vertex_arrays_ids);
+					self.draw_buffer_group(groupe, &mut gl_material_ids, &mut gl_texture_ids, &mut vertex_arrays_ids, &mut gl_instance_ids);
 				});
 			}
 		}
 
+		// buffers of removed instances
+		let alive: HashSet<Uuid> = (&instances_coll).join().map(|instances| instances.uuid).collect();
+		gl_instance_ids.retain(|uuid, _| alive.contains(uuid));
+		self.invalid_instances.retain(|uuid, _| alive.contains(uuid));
+
 		self.swap_buffers().unwrap();
 	}
 }
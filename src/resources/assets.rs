@@ -0,0 +1,510 @@
+extern crate specs;
+extern crate typer;
+
+use self::specs::shrev::ReaderId;
+use self::typer::rusttype::Font;
+use super::{BackgroundLoader, LayerEvent, LoadHandle, Resource, ResourceManager, Scene};
+use core::{SharedGeometry, SharedTexture2D, Texture2D};
+use helpers::{load_obj_from_bytes, obj_material_libs, GltfData};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub enum Asset {
+	Texture(SharedTexture2D),
+	Shader(Arc<String>),
+	Gltf(Arc<GltfData>),
+	Obj(Vec<SharedGeometry>),
+	Font(Font<'static>),
+	Scene(Arc<Scene>),
+}
+
+pub trait FromAsset: Sized {
+	fn from_asset(asset: Asset) -> Option<Self>;
+}
+
+impl FromAsset for SharedTexture2D {
+	fn from_asset(asset: Asset) -> Option<Self> {
+		match asset {
+			Asset::Texture(texture) => Some(texture),
+			_ => None,
+		}
+	}
+}
+
+impl FromAsset for Arc<String> {
+	fn from_asset(asset: Asset) -> Option<Self> {
+		match asset {
+			Asset::Shader(code) => Some(code),
+			_ => None,
+		}
+	}
+}
+
+impl FromAsset for Arc<GltfData> {
+	fn from_asset(asset: Asset) -> Option<Self> {
+		match asset {
+			Asset::Gltf(gltf) => Some(gltf),
+			_ => None,
+		}
+	}
+}
+
+impl FromAsset for Vec<SharedGeometry> {
+	fn from_asset(asset: Asset) -> Option<Self> {
+		match asset {
+			Asset::Obj(geometries) => Some(geometries),
+			_ => None,
+		}
+	}
+}
+
+impl FromAsset for Font<'static> {
+	fn from_asset(asset: Asset) -> Option<Self> {
+		match asset {
+			Asset::Font(font) => Some(font),
+			_ => None,
+		}
+	}
+}
+
+impl FromAsset for Arc<Scene> {
+	fn from_asset(asset: Asset) -> Option<Self> {
+		match asset {
+			Asset::Scene(scene) => Some(scene),
+			_ => None,
+		}
+	}
+}
+
+impl Asset {
+	// decoded CPU side bytes, font and scene memory is not tracked
+	pub fn memory_usage(&self) -> MemoryUsage {
+		let mut usage = MemoryUsage::default();
+		match self {
+			Asset::Texture(texture) => {
+				let mut texture = texture.clone();
+				let texture = texture.lock().unwrap();
+				usage.textures = texture.get_texture_data_ref().map_or(0, |data| data.memory_size());
+			}
+			Asset::Shader(code) => usage.other = code.len(),
+			Asset::Gltf(gltf) => usage.other = gltf.memory_size(),
+			Asset::Obj(geometries) => {
+				usage.geometries = geometries
+					.iter()
+					.map(|geometry| geometry.clone().lock().unwrap().memory_size())
+					.sum();
+			}
+			Asset::Font(_) => {}
+			Asset::Scene(_) => {}
+		}
+		usage
+	}
+
+	// only referenced by the asset cache
+	pub fn is_unused(&self) -> bool {
+		match self {
+			Asset::Texture(texture) => texture.ref_count() == 1,
+			Asset::Shader(code) => Arc::strong_count(code) == 1,
+			Asset::Gltf(gltf) => Arc::strong_count(gltf) == 1,
+			Asset::Obj(geometries) => geometries.iter().all(|geometry| geometry.ref_count() == 1),
+			Asset::Font(_) => false,
+			Asset::Scene(scene) => Arc::strong_count(scene) == 1,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MemoryUsage {
+	// raw resource bytes
+	pub resources: usize,
+	pub textures: usize,
+	pub geometries: usize,
+	pub other: usize,
+}
+
+impl MemoryUsage {
+	pub fn total(&self) -> usize {
+		self.resources + self.textures + self.geometries + self.other
+	}
+
+	fn add(&mut self, other: &MemoryUsage) {
+		self.resources += other.resources;
+		self.textures += other.textures;
+		self.geometries += other.geometries;
+		self.other += other.other;
+	}
+}
+
+struct CachedAsset {
+	asset: Asset,
+	last_used: usize,
+}
+
+pub type AssetLoader = fn(&Resource, &[u8], &Dependencies) -> Result<Asset, String>;
+// names and data of resources referenced by the loaded one in scanner order, missing ones are left out
+pub type Dependencies = Vec<(String, Vec<u8>)>;
+// names of resources a loader reads besides the loaded one
+pub type DependencyScanner = fn(&Resource, &[u8]) -> Vec<String>;
+
+// Typed access to package resources, `Resource::data_type` selects the loader
+pub struct AssetManager {
+	pub resource_manager: ResourceManager,
+	loaders: HashMap<String, AssetLoader>,
+	dependencies: HashMap<String, DependencyScanner>,
+	assets: HashMap<String, CachedAsset>,
+	pending: Vec<(String, LoadHandle<Asset>)>,
+	layer_reader: ReaderId<LayerEvent>,
+	// failed reloads of changed resources, returned by `update`
+	errors: Vec<(String, String)>,
+	// access counter for LRU eviction
+	tick: usize,
+	memory_budget: Option<usize>,
+}
+
+impl AssetManager {
+	pub fn new(mut resource_manager: ResourceManager) -> Self {
+		let layer_reader = resource_manager.register_reader();
+		let mut manager = Self {
+			resource_manager,
+			loaders: HashMap::new(),
+			dependencies: HashMap::new(),
+			assets: HashMap::new(),
+			pending: Vec::new(),
+			layer_reader,
+			errors: Vec::new(),
+			tick: 0,
+			memory_budget: None,
+		};
+
+		manager.register_loader("texture", load_texture);
+		manager.register_loader("shader", load_shader);
+		manager.register_loader("gltf", load_gltf);
+		manager.register_loader("obj", load_obj);
+		manager.register_loader("font", load_font);
+		manager.register_loader("scene", load_scene_data);
+		manager.register_dependencies("obj", obj_dependencies);
+		manager
+	}
+
+	pub fn register_loader(&mut self, data_type: &str, loader: AssetLoader) {
+		self.loaders.insert(data_type.to_string(), loader);
+	}
+
+	// changes of dependencies don't reload assets using them
+	pub fn register_dependencies(&mut self, data_type: &str, scanner: DependencyScanner) {
+		self.dependencies.insert(data_type.to_string(), scanner);
+	}
+
+	pub fn load(&mut self, name: &str) -> Result<Asset, String> {
+		self.sync_layers();
+
+		self.tick += 1;
+		if let Some(cached) = self.assets.get_mut(name) {
+			cached.last_used = self.tick;
+			return Ok(cached.asset.clone());
+		}
+
+		let asset = self.decode(name)?;
+		self.cache(name.to_string(), asset.clone());
+		Ok(asset)
+	}
+
+	// decodes the asset on the loader pool, it is cached by `update` when ready
+	pub fn request(&mut self, loader: &BackgroundLoader, name: &str) -> Result<(), String> {
+		if self.is_cached(name) || self.is_pending(name) {
+			return Ok(());
+		}
+
+		self.resource_manager.restore_resource_data(name)?;
+		let dependencies = self.read_dependencies(name)?;
+		let handle = {
+			let (asset_loader, resource, data) = self.get_loader(name)?;
+			let resource = resource.clone();
+			let data = data.clone();
+			loader.spawn(move || asset_loader(&resource, &data, &dependencies))
+		};
+
+		self.pending.push((name.to_string(), handle));
+		Ok(())
+	}
+
+	pub fn is_pending(&self, name: &str) -> bool {
+		self.pending.iter().any(|(pending, _)| pending == name)
+	}
+
+	// moves finished requests to the cache and keeps the memory budget, returns failed requests
+	pub fn update(&mut self) -> Vec<(String, String)> {
+		self.sync_layers();
+
+		let mut errors: Vec<_> = self.errors.drain(..).collect();
+		let mut pending = Vec::with_capacity(self.pending.len());
+
+		let requests: Vec<_> = self.pending.drain(..).collect();
+		for (name, handle) in requests {
+			match handle.take() {
+				None => pending.push((name, handle)),
+				Some(Ok(asset)) => self.cache(name, asset),
+				Some(Err(err)) => errors.push((name, err)),
+			}
+		}
+
+		self.pending = pending;
+
+		if let Some(budget) = self.memory_budget {
+			self.trim(budget);
+		}
+		errors
+	}
+
+	// None - unlimited
+	pub fn set_memory_budget(&mut self, budget: Option<usize>) {
+		self.memory_budget = budget;
+	}
+
+	pub fn get_memory_budget(&self) -> Option<usize> {
+		self.memory_budget
+	}
+
+	pub fn memory_usage(&self) -> MemoryUsage {
+		let mut usage = MemoryUsage::default();
+		usage.resources = self.resource_manager.memory_usage();
+		self.assets.values().for_each(|cached| usage.add(&cached.asset.memory_usage()));
+		usage
+	}
+
+	// resident memory of every loaded package, assets are counted to the package serving their resource
+	pub fn memory_report(&self) -> Vec<(String, MemoryUsage)> {
+		let mut report: Vec<(String, MemoryUsage)> = self
+			.resource_manager
+			.get_layers()
+			.into_iter()
+			.map(|layer| {
+				let mut usage = MemoryUsage::default();
+				usage.resources = self.resource_manager.package_memory_usage(layer);
+				(layer.to_string(), usage)
+			})
+			.collect();
+
+		for (name, cached) in self.assets.iter() {
+			let layer = self.resource_manager.get_resource_layer(name);
+			if let Some((_, usage)) = report.iter_mut().find(|(package, _)| Some(package.as_str()) == layer) {
+				usage.add(&cached.asset.memory_usage());
+			}
+		}
+
+		report
+	}
+
+	// evicts least recently used unused assets first, then raw resource data, returns freed bytes
+	pub fn trim(&mut self, budget: usize) -> usize {
+		let mut usage = self.memory_usage().total();
+		if usage <= budget {
+			return 0;
+		}
+
+		let mut unused: Vec<(usize, String)> = self
+			.assets
+			.iter()
+			.filter(|(_, cached)| cached.asset.is_unused())
+			.map(|(name, cached)| (cached.last_used, name.clone()))
+			.collect();
+		unused.sort();
+
+		let mut freed = 0;
+		for (_, name) in unused {
+			if usage <= budget {
+				break;
+			}
+			if let Some(cached) = self.assets.remove(&name) {
+				let size = cached.asset.memory_usage().total();
+				usage -= size;
+				freed += size;
+			}
+		}
+
+		let resources = self.resource_manager.memory_usage();
+		let others = usage - resources;
+		freed + self.resource_manager.trim(budget.saturating_sub(others))
+	}
+
+	pub fn get<T: FromAsset>(&mut self, name: &str) -> Result<T, String> {
+		let asset = self.load(name)?;
+		T::from_asset(asset).ok_or(format!("resource '{}' has wrong asset type", name))
+	}
+
+	fn get_loader(&self, name: &str) -> Result<(AssetLoader, &Resource, &Vec<u8>), String> {
+		let resource = self
+			.resource_manager
+			.get_resource(name)
+			.ok_or(format!("resource '{}' not exists", name))?;
+		let loader = self
+			.loaders
+			.get(&resource.data_type)
+			.ok_or(format!("no loader for data type '{}'", resource.data_type))?;
+		let data = self
+			.resource_manager
+			.get_resource_data(name)
+			.ok_or(format!("resource '{}' is not loaded", name))?;
+
+		Ok((*loader, resource, data))
+	}
+
+	// changed textures and meshes are updated in place, so materials and entities using them
+	// pick up the change, other assets are replaced in the cache
+	fn sync_layers(&mut self) {
+		let changed: Vec<String> = self
+			.resource_manager
+			.read_events(&mut self.layer_reader)
+			.filter_map(|event| match event {
+				LayerEvent::Changed(names) => Some(names.clone()),
+				_ => None,
+			})
+			.flatten()
+			.collect();
+
+		self.pending.retain(|(name, _)| !changed.contains(name));
+
+		for name in changed {
+			if !self.assets.contains_key(&name) {
+				continue;
+			}
+			if self.resource_manager.get_resource_layer(&name).is_none() {
+				self.assets.remove(&name);
+				continue;
+			}
+
+			// broken file keeps the old asset, it is reloaded on next change
+			let update = match self.decode(&name) {
+				Ok(asset) => asset,
+				Err(err) => {
+					self.errors.push((name, err));
+					continue;
+				}
+			};
+
+			let cached = self.assets.get_mut(&name).unwrap();
+			match (&mut cached.asset, update) {
+				(Asset::Texture(texture), Asset::Texture(mut update)) => {
+					let data = update.lock().unwrap().get_texture_data_ref().cloned();
+					let mut texture = texture.lock().unwrap();
+					texture.set_texture_data(data);
+					texture.need_update = true;
+				}
+				(Asset::Obj(geometries), Asset::Obj(updates)) if geometries.len() == updates.len() => {
+					for (geometry, mut update) in geometries.iter_mut().zip(updates.into_iter()) {
+						std::mem::swap(&mut *geometry.lock().unwrap(), &mut *update.lock().unwrap());
+					}
+				}
+				(asset, update) => *asset = update,
+			}
+		}
+	}
+
+	fn decode(&mut self, name: &str) -> Result<Asset, String> {
+		self.resource_manager.restore_resource_data(name)?;
+		let dependencies = self.read_dependencies(name)?;
+		let (loader, resource, data) = self.get_loader(name)?;
+		loader(resource, data, &dependencies)
+	}
+
+	fn read_dependencies(&mut self, name: &str) -> Result<Dependencies, String> {
+		let names = {
+			let (_, resource, data) = self.get_loader(name)?;
+			match self.dependencies.get(&resource.data_type) {
+				Some(scanner) => scanner(resource, data),
+				None => return Ok(Dependencies::new()),
+			}
+		};
+
+		let mut dependencies = Dependencies::new();
+		for dependency in names {
+			if self.resource_manager.restore_resource_data(&dependency).is_err() {
+				continue;
+			}
+			if let Some(data) = self.resource_manager.get_resource_data(&dependency) {
+				dependencies.push((dependency, data.clone()));
+			}
+		}
+		Ok(dependencies)
+	}
+
+	fn cache(&mut self, name: String, asset: Asset) {
+		self.tick += 1;
+		let last_used = self.tick;
+		self.assets.insert(name, CachedAsset { asset, last_used });
+	}
+
+	// resource name of a cached texture
+	pub fn find_texture(&self, texture: &SharedTexture2D) -> Option<&str> {
+		self.assets.iter().find_map(|(name, cached)| match &cached.asset {
+			Asset::Texture(e) if e == texture => Some(name.as_str()),
+			_ => None,
+		})
+	}
+
+	// resource name and mesh index of a cached obj geometry
+	pub fn find_geometry(&self, geometry: &SharedGeometry) -> Option<(&str, usize)> {
+		self.assets.iter().find_map(|(name, cached)| match &cached.asset {
+			Asset::Obj(geometries) => geometries
+				.iter()
+				.position(|e| e.ptr_eq(geometry))
+				.map(|index| (name.as_str(), index)),
+			_ => None,
+		})
+	}
+
+	pub fn is_cached(&self, name: &str) -> bool {
+		self.assets.contains_key(name)
+	}
+
+	pub fn unload(&mut self, name: &str) {
+		self.assets.remove(name);
+	}
+
+	pub fn clear(&mut self) {
+		self.assets.clear();
+	}
+}
+
+fn load_texture(_: &Resource, data: &[u8], _: &Dependencies) -> Result<Asset, String> {
+	let texture = Texture2D::from_bytes(data)?;
+	Ok(Asset::Texture(SharedTexture2D::new(texture)))
+}
+
+fn load_shader(resource: &Resource, data: &[u8], _: &Dependencies) -> Result<Asset, String> {
+	let code = String::from_utf8(data.to_vec()).or(Err(format!("shader is not utf8: {}", resource.name_str())))?;
+	Ok(Asset::Shader(Arc::new(code)))
+}
+
+fn load_gltf(_: &Resource, data: &[u8], _: &Dependencies) -> Result<Asset, String> {
+	let gltf = GltfData::from_slice(data).or_else(|e| Err(format!("{}", e)))?;
+	Ok(Asset::Gltf(Arc::new(gltf)))
+}
+
+// material libraries next to the obj
+fn obj_dependencies(resource: &Resource, data: &[u8]) -> Vec<String> {
+	let dir = resource.name.parent().map_or(PathBuf::new(), |dir| dir.to_path_buf());
+	obj_material_libs(data)
+		.iter()
+		.map(|lib| dir.join(lib).to_string_lossy().to_string())
+		.collect()
+}
+
+fn load_obj(_: &Resource, data: &[u8], dependencies: &Dependencies) -> Result<Asset, String> {
+	// `mtllib` order, later libraries override materials of earlier ones like in `load_obj`
+	let mtls: Vec<&[u8]> = dependencies.iter().map(|(_, mtl)| &mtl[..]).collect();
+	let geometries = load_obj_from_bytes(data, &mtls)?;
+	Ok(Asset::Obj(geometries.into_iter().map(SharedGeometry::new).collect()))
+}
+
+fn load_font(resource: &Resource, data: &[u8], _: &Dependencies) -> Result<Asset, String> {
+	let font = Font::from_bytes(data.to_vec()).or(Err(format!("wrong font: {}", resource.name_str())))?;
+	Ok(Asset::Font(font))
+}
+
+fn load_scene_data(resource: &Resource, data: &[u8], _: &Dependencies) -> Result<Asset, String> {
+	let scene = Scene::from_slice(data).or_else(|e| Err(format!("wrong scene {}: {}", resource.name_str(), e)))?;
+	Ok(Asset::Scene(Arc::new(scene)))
+}
@@ -0,0 +1,149 @@
+extern crate rayon;
+
+use self::rayon::{ThreadPool, ThreadPoolBuilder};
+use core::{SharedTexture2D, TextureData};
+use helpers::GltfData;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+
+// Result of a background job, polled from the render thread
+pub struct LoadHandle<T> {
+	state: Arc<(Mutex<Option<Result<T, String>>>, Condvar)>,
+}
+
+impl<T> LoadHandle<T> {
+	pub fn is_done(&self) -> bool {
+		(self.state.0).lock().unwrap().is_some()
+	}
+
+	// non-blocking, returns the result once
+	pub fn take(&self) -> Option<Result<T, String>> {
+		(self.state.0).lock().unwrap().take()
+	}
+
+	pub fn wait(self) -> Result<T, String> {
+		let (lock, condvar) = &*self.state;
+		let mut result = lock.lock().unwrap();
+		loop {
+			if let Some(result) = result.take() {
+				return result;
+			}
+			result = condvar.wait(result).unwrap();
+		}
+	}
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LoadProgress {
+	total: Arc<AtomicUsize>,
+	done: Arc<AtomicUsize>,
+}
+
+impl LoadProgress {
+	pub fn total(&self) -> usize {
+		self.total.load(Ordering::SeqCst)
+	}
+
+	pub fn done(&self) -> usize {
+		self.done.load(Ordering::SeqCst)
+	}
+
+	pub fn is_complete(&self) -> bool {
+		self.done() >= self.total()
+	}
+
+	// 0.0 .. 1.0
+	pub fn get(&self) -> f32 {
+		let total = self.total();
+		if total == 0 {
+			return 1.0;
+		}
+		self.done() as f32 / total as f32
+	}
+}
+
+// Reads and decodes resources on its own thread pool, GPU upload stays on the render thread
+pub struct BackgroundLoader {
+	pool: Arc<ThreadPool>,
+	progress: LoadProgress,
+}
+
+impl BackgroundLoader {
+	pub fn new() -> Self {
+		Self::with_threads(0)
+	}
+
+	// 0 - one thread per cpu
+	pub fn with_threads(num_threads: usize) -> Self {
+		let pool = ThreadPoolBuilder::new()
+			.num_threads(num_threads)
+			.thread_name(|i| format!("loader-{}", i))
+			.build()
+			.unwrap();
+
+		Self {
+			pool: Arc::new(pool),
+			progress: LoadProgress::default(),
+		}
+	}
+
+	pub fn progress(&self) -> LoadProgress {
+		self.progress.clone()
+	}
+
+	pub fn spawn<T, F>(&self, job: F) -> LoadHandle<T>
+	where
+		T: Send + 'static,
+		F: FnOnce() -> Result<T, String> + Send + 'static,
+	{
+		let state = Arc::new((Mutex::new(None), Condvar::new()));
+		let handle = LoadHandle { state: state.clone() };
+		let progress = self.progress.clone();
+
+		progress.total.fetch_add(1, Ordering::SeqCst);
+
+		self.pool.spawn(move || {
+			let result = catch_unwind(AssertUnwindSafe(job)).unwrap_or(Err("load job panicked".to_string()));
+
+			let (lock, condvar) = &*state;
+			*lock.lock().unwrap() = Some(result);
+			progress.done.fetch_add(1, Ordering::SeqCst);
+			condvar.notify_all();
+		});
+
+		handle
+	}
+
+	// texture is usable right away, it is uploaded on first bind after decoding
+	pub fn load_texture(&self, texture: &SharedTexture2D) -> LoadHandle<()> {
+		let mut texture = texture.clone();
+		let path = {
+			let mut texture = texture.lock().unwrap();
+			texture.pending = true;
+			texture.path.clone()
+		};
+
+		self.spawn(move || {
+			let decode = || -> Result<TextureData, String> {
+				let path = path.ok_or("missing path for load image".to_string())?;
+				TextureData::from_path(&path)
+			};
+			let result = decode();
+
+			// failed loads leave the texture without data, but not pending
+			let mut texture = texture.lock().unwrap();
+			texture.pending = false;
+			let data = result?;
+			texture.set_texture_data(Some(data));
+			texture.need_update = true;
+			Ok(())
+		})
+	}
+
+	// entities are created from the result with `load_gltf_data`
+	pub fn load_gltf(&self, path: PathBuf) -> LoadHandle<GltfData> {
+		self.spawn(move || GltfData::from_path(&path).or_else(|e| Err(format!("{}", e))))
+	}
+}
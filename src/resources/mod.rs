@@ -0,0 +1,9 @@
+mod resource_manager;
+mod assets;
+mod loader;
+mod scene;
+
+pub use self::resource_manager::*;
+pub use self::assets::*;
+pub use self::loader::*;
+pub use self::scene::*;
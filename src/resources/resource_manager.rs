@@ -0,0 +1,1326 @@
+extern crate flate2;
+extern crate rayon;
+extern crate serde;
+extern crate serde_json;
+extern crate sha2;
+extern crate specs;
+extern crate tar;
+extern crate zstd;
+
+use std::io::prelude::*;
+// use std::fs::File;
+use self::tar::{Archive, Builder, Header};
+
+use self::flate2::read::GzDecoder;
+use self::flate2::write::GzEncoder;
+use self::rayon::prelude::*;
+use self::serde::{Deserialize, Serialize};
+use self::sha2::{Digest, Sha256};
+use self::specs::shrev::{EventChannel, EventIterator, ReaderId};
+use super::{BackgroundLoader, LoadHandle};
+use core::{bake_texture, MeshBake, TextureBake};
+use helpers::{bake_obj, read_material_libs};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::SystemTime;
+
+// #[derive(Serialize, Deserialize, Debug)]
+// pub struct LoadedResource {
+// 	pub name: String,
+// 	pub data_type: String,
+// 	pub path: PathBuf,
+// 	pub content_type: String,
+// 	pub bin: Option<Vec<u8>>,
+// }
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Resource {
+	pub name: PathBuf,
+	pub data_type: String,
+	pub path: PathBuf,
+	// manifest, filled by `build`
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub size: Option<u64>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub hash: Option<String>,
+	// hash of the source file when a bake step changed the data, checked instead of the manifest
+	// when the resource is read from the source directory
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub source_hash: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+	None,
+	Gzip,
+	Zstd,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Package {
+	pub name: String,
+	#[serde(default)]
+	pub priority: i32,
+	// unset packages take the compression of the build, see `get_compression`
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub compression: Option<Compression>,
+	// applied to "texture" resources by `build_package_data`
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub textures: Option<TextureBake>,
+	// applied to "obj" resources by `build_package_data`
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub meshes: Option<MeshBake>,
+	pub resources: Vec<Resource>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PackageList {
+	pub packages: Vec<Package>,
+}
+
+// resources of a package read by a background job
+pub struct PackageData {
+	pub package: String,
+	pub mode: LoadMode,
+	pub resources: Vec<(String, Vec<u8>)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoadMode {
+	// resources are read from the source files of the package list, changes are picked by `poll_changes`
+	Directory,
+	// packages are read from archives built by resource-manager next to the package list
+	Archive,
+}
+
+// Every loaded package is a layer, a resource is served by the loaded package
+// with the highest priority, on equal priority the last loaded one wins
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayerEvent {
+	Added(String),
+	Removed(String),
+	// resources served by another layer now, reloaded, changed on disk or not available anymore
+	Changed(Vec<String>),
+}
+
+#[derive(Debug)]
+struct ResourceData {
+	// None when evicted
+	data: Option<Vec<u8>>,
+	last_used: AtomicUsize,
+	// source file modification time, for layers read from disk
+	modified: Option<SystemTime>,
+}
+
+// where evicted data of a layer is read again from
+#[derive(Debug, Clone, PartialEq)]
+enum LayerSource {
+	// source files of the package, changes are picked by `poll_changes`
+	Directory,
+	// package archive, single resources are extracted again
+	Archive(PathBuf),
+	// bytes passed by the caller, data can't be evicted
+	Memory,
+}
+
+#[derive(Debug)]
+struct Layer {
+	name: String,
+	source: LayerSource,
+	resources: HashMap<String, ResourceData>,
+}
+
+#[derive(Debug)]
+pub struct ResourceManager {
+	package_list: Option<PackageList>,
+	package_list_path: Option<PathBuf>,
+	// base dirs of packages mounted from other lists or directories
+	package_dirs: HashMap<String, PathBuf>,
+	// loaded layers in load order
+	data_loaded: Vec<Layer>,
+	// resource name -> layer it is served from
+	layers: HashMap<String, String>,
+	events: EventChannel<LayerEvent>,
+	// access counter for LRU eviction
+	tick: AtomicUsize,
+	mode: LoadMode,
+}
+
+impl Resource {
+	pub fn name_str(&self) -> String {
+		(*self.name.to_string_lossy()).to_string()
+	}
+
+	pub fn set_manifest(&mut self, data: &[u8]) {
+		self.size = Some(data.len() as u64);
+		self.hash = Some(content_hash(data));
+	}
+
+	// resources without manifest are not checked
+	pub fn verify(&self, data: &[u8]) -> Result<(), String> {
+		if let Some(size) = self.size {
+			if (data.len() as u64) < size {
+				return Err(format!(
+					"resource '{}' is truncated: expected {} bytes, got {}",
+					self.name_str(),
+					size,
+					data.len()
+				));
+			}
+			if data.len() as u64 != size {
+				return Err(format!(
+					"resource '{}' is corrupted: expected {} bytes, got {}",
+					self.name_str(),
+					size,
+					data.len()
+				));
+			}
+		}
+
+		if let Some(hash) = &self.hash {
+			if content_hash(data) != *hash {
+				return Err(format!("resource '{}' is corrupted: hash mismatch", self.name_str()));
+			}
+		}
+
+		Ok(())
+	}
+
+	// checks a source file, baked resources have a manifest of the baked data
+	pub fn verify_source(&self, data: &[u8]) -> Result<(), String> {
+		match &self.source_hash {
+			Some(hash) if content_hash(data) != *hash => {
+				Err(format!("resource '{}' is corrupted: source hash mismatch", self.name_str()))
+			}
+			Some(_) => Ok(()),
+			None => self.verify(data),
+		}
+	}
+}
+
+impl Default for Compression {
+	fn default() -> Self {
+		Compression::None
+	}
+}
+
+impl Compression {
+	pub fn from_name(name: &str) -> Result<Self, String> {
+		match name {
+			"none" => Ok(Compression::None),
+			"gzip" => Ok(Compression::Gzip),
+			"zstd" => Ok(Compression::Zstd),
+			_ => Err(format!("unknown compression '{}'", name)),
+		}
+	}
+
+	pub fn extension(&self) -> &str {
+		match self {
+			Compression::None => "tar",
+			Compression::Gzip => "tar.gz",
+			Compression::Zstd => "tar.zst",
+		}
+	}
+}
+
+impl Package {
+	pub fn get_compression(&self) -> Compression {
+		self.compression.unwrap_or_default()
+	}
+
+	pub fn file_name(&self) -> String {
+		format!("{}.{}", self.name, self.get_compression().extension())
+	}
+}
+
+// sha256, hex encoded
+pub fn content_hash(data: &[u8]) -> String {
+	Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// packs resource files into a package archive and fills their manifest
+pub fn build_package_data(package: &mut Package) -> Result<Vec<u8>, String> {
+	let mut builder = Builder::new(Vec::new());
+
+	for res in package.resources.iter_mut() {
+		let source = std::fs::read(&res.path).or(Err(format!("cant read file: {}", res.path.to_string_lossy())))?;
+		let baked = match res.data_type.as_str() {
+			"texture" => package.textures.as_ref().map(|bake| bake_texture(&source, bake)),
+			"obj" => package.meshes.as_ref().map(|bake| {
+				let mtls = read_material_libs(&source, res.path.parent().unwrap_or(Path::new("")));
+				bake_obj(&source, &mtls.iter().map(|mtl| &mtl[..]).collect::<Vec<_>>(), bake)
+			}),
+			_ => None,
+		}
+		.transpose()
+		.or_else(|e| Err(format!("{}: {}", res.path.to_string_lossy(), e)))?;
+
+		res.source_hash = baked.as_ref().map(|_| content_hash(&source));
+		let data = baked.unwrap_or(source);
+		res.set_manifest(&data);
+
+		let mut header = Header::new_gnu();
+		header.set_size(data.len() as u64);
+		header.set_mode(0o644);
+		header.set_cksum();
+		builder
+			.append_data(&mut header, &res.name, &data[..])
+			.or_else(|e| Err(format!("{}", e)))?;
+	}
+
+	let data = builder.into_inner().or_else(|e| Err(format!("{}", e)))?;
+	compress(&data, package.get_compression())
+}
+
+pub fn compress(data: &[u8], compression: Compression) -> Result<Vec<u8>, String> {
+	match compression {
+		Compression::None => Ok(data.to_vec()),
+		Compression::Gzip => {
+			let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+			encoder.write_all(data).or_else(|e| Err(format!("{}", e)))?;
+			encoder.finish().or_else(|e| Err(format!("{}", e)))
+		}
+		Compression::Zstd => zstd::encode_all(data, 0).or_else(|e| Err(format!("{}", e))),
+	}
+}
+
+pub fn read_package(package: &Package, data: &[u8]) -> Result<HashMap<String, Vec<u8>>, String> {
+	let data = decompress(data, package.get_compression())
+		.or_else(|e| Err(format!("package '{}' is corrupted: {}", package.name, e)))?;
+	let mut archive = Archive::new(&data[..]);
+
+	let entries = archive
+		.entries()
+		.or_else(|e| Err(format!("package '{}' is corrupted: {}", package.name, e)))?;
+
+	let mut resources = HashMap::new();
+	for file in entries {
+		let mut file = file.or_else(|e| Err(format!("package '{}' is truncated or corrupted: {}", package.name, e)))?;
+
+		let file_path = { PathBuf::from(&*file.path().or(Err("tar file path error"))?) };
+
+		let res = package
+			.resources
+			.iter()
+			.find(|res| res.name == file_path)
+			.ok_or(format!("wrong file path {:?}", file_path))?;
+
+		let mut data = Vec::new();
+		file.read_to_end(&mut data)
+			.or_else(|e| Err(format!("package '{}' is truncated or corrupted: {}", package.name, e)))?;
+
+		res.verify(&data)?;
+		resources.insert(res.name_str(), data);
+	}
+
+	if let Some(res) = package
+		.resources
+		.iter()
+		.find(|res| resources.get(&res.name_str()).is_none())
+	{
+		return Err(format!(
+			"package '{}' is truncated: missing resource '{}'",
+			package.name,
+			res.name_str()
+		));
+	}
+
+	Ok(resources)
+}
+
+// extracts a single resource, the whole archive is decompressed on the way
+pub fn read_package_entry(package: &Package, data: &[u8], name: &str) -> Result<Vec<u8>, String> {
+	let res = package
+		.resources
+		.iter()
+		.find(|res| res.name_str() == name)
+		.ok_or(format!("resource '{}' not exists", name))?;
+
+	let data = decompress(data, package.get_compression())
+		.or_else(|e| Err(format!("package '{}' is corrupted: {}", package.name, e)))?;
+	let mut archive = Archive::new(&data[..]);
+
+	let entries = archive
+		.entries()
+		.or_else(|e| Err(format!("package '{}' is corrupted: {}", package.name, e)))?;
+
+	for file in entries {
+		let mut file = file.or_else(|e| Err(format!("package '{}' is truncated or corrupted: {}", package.name, e)))?;
+		if *file.path().or(Err("tar file path error"))? != *res.name {
+			continue;
+		}
+
+		let mut data = Vec::new();
+		file.read_to_end(&mut data)
+			.or_else(|e| Err(format!("package '{}' is truncated or corrupted: {}", package.name, e)))?;
+
+		res.verify(&data)?;
+		return Ok(data);
+	}
+
+	Err(format!("package '{}' is truncated: missing resource '{}'", package.name, name))
+}
+
+pub fn decompress(data: &[u8], compression: Compression) -> Result<Vec<u8>, String> {
+	match compression {
+		Compression::None => Ok(data.to_vec()),
+		Compression::Gzip => {
+			let mut result = Vec::new();
+			GzDecoder::new(data)
+				.read_to_end(&mut result)
+				.or_else(|e| Err(format!("{}", e)))?;
+			Ok(result)
+		}
+		Compression::Zstd => zstd::decode_all(data).or_else(|e| Err(format!("{}", e))),
+	}
+}
+
+impl ResourceManager {
+	pub fn new() -> Self {
+		Self {
+			package_list: None,
+			package_list_path: None,
+			package_dirs: HashMap::new(),
+			data_loaded: Vec::new(),
+			layers: HashMap::new(),
+			events: EventChannel::new(),
+			tick: AtomicUsize::new(0),
+			mode: LoadMode::default(),
+		}
+	}
+
+	pub fn parse_package_list(data: &str) -> Result<PackageList, String> {
+		let package_list: Result<PackageList, serde_json::Error> = serde_json::from_str(data);
+		package_list.or_else(|e| Err(format!("{:?}", e)))
+	}
+
+	pub fn set_package_list(&mut self, package_list: PackageList) {
+		self.package_list = Some(package_list);
+	}
+
+	pub fn load_package_list(&mut self, path: &PathBuf) -> Result<(), String> {
+		let data = std::fs::read_to_string(path)
+			.or(Err(format!("incorrect path:{}", path.to_string_lossy())))?;
+		self.package_list = Some(Self::parse_package_list(&data)?);
+		self.package_list_path = Some(path.clone());
+
+		Ok(())
+	}
+
+	pub fn set_package_list_from_str(&mut self, data: &str) -> Result<(), String> {
+		let data = ResourceManager::parse_package_list(data)?;
+		self.set_package_list(data);
+		Ok(())
+	}
+
+	pub fn get_package_list(&self) -> Result<&PackageList, String> {
+		Ok(self.package_list.as_ref().ok_or("package_list is None")?)
+	}
+
+	pub fn set_mode(&mut self, mode: LoadMode) {
+		self.mode = mode;
+	}
+
+	pub fn get_mode(&self) -> LoadMode {
+		self.mode
+	}
+
+	// adds packages of another list (DLC, mods), packages are not loaded
+	pub fn mount_package_list(&mut self, path: &PathBuf) -> Result<(), String> {
+		let data = std::fs::read_to_string(path)
+			.or(Err(format!("incorrect path:{}", path.to_string_lossy())))?;
+		let package_list = Self::parse_package_list(&data)?;
+
+		let mut dir = path.clone();
+		dir.pop();
+
+		for package in package_list.packages {
+			self.package_dirs.insert(package.name.clone(), dir.clone());
+			self.mount_package(package);
+		}
+
+		Ok(())
+	}
+
+	// a package with the same name is replaced and its data is unloaded
+	pub fn mount_package(&mut self, package: Package) {
+		self.unload_package(&package.name);
+
+		let packages = &mut self
+			.package_list
+			.get_or_insert(PackageList { packages: Vec::new() })
+			.packages;
+		packages.retain(|e| e.name != package.name);
+		packages.push(package);
+	}
+
+	// loose files shadow packaged resources by their relative path
+	pub fn mount_directory(&mut self, name: &str, dir: &PathBuf, priority: i32) -> Result<(), String> {
+		let mut resources = Vec::new();
+		collect_resources(dir, dir, &mut resources)?;
+		resources.sort_by(|a, b| a.name.cmp(&b.name));
+
+		self.package_dirs.insert(name.to_string(), dir.clone());
+		self.mount_package(Package {
+			name: name.to_string(),
+			priority,
+			compression: None,
+			textures: None,
+			meshes: None,
+			resources,
+		});
+		self.load_package_from_disk(name)
+	}
+
+	pub fn clear(&mut self) {
+		let loaded: Vec<String> = self.data_loaded.iter().map(|layer| layer.name.clone()).collect();
+		for name in loaded {
+			self.unload_package(&name);
+		}
+
+		self.package_list = None;
+		self.package_list_path = None;
+		self.package_dirs.clear();
+	}
+
+	pub fn add_package_data(&mut self, data: &[u8], name: &str) -> Result<(), String> {
+		let package = self
+			.get_package_list()?
+			.packages
+			.iter()
+			.find(|package| package.name == name)
+			.ok_or(format!("package '{}' not exists", name))?;
+
+		let resources = read_package(package, data)?;
+		self.set_layer(name, Some(resources), LayerSource::Memory);
+		Ok(())
+	}
+
+	// reads the archive built by resource-manager next to the package list,
+	// evicted resources are extracted from it again
+	pub fn load_package_archive(&mut self, name: &str) -> Result<(), String> {
+		let path = self.get_package_archive_path(name)?;
+		let data = std::fs::read(&path).or(Err(format!("cant read file: {}", path.to_string_lossy())))?;
+		let resources = read_package(self.get_package(name)?, &data)?;
+		self.set_layer(name, Some(resources), LayerSource::Archive(path));
+		Ok(())
+	}
+
+	// reads packages from source files or from archives depending on the load mode
+	pub fn load_package(&mut self, name: &str) -> Result<(), String> {
+		match self.mode {
+			LoadMode::Directory => self.load_package_from_disk(name),
+			LoadMode::Archive => self.load_package_archive(name),
+		}
+	}
+
+	pub fn get_resource(&self, name: &str) -> Option<&Resource> {
+		let packages = &self.package_list.as_ref()?.packages;
+
+		let package = match self.layers.get(name) {
+			Some(layer) => packages.iter().find(|package| package.name == *layer)?,
+			None => packages
+				.iter()
+				.filter(|package| package.resources.iter().any(|res| res.name_str() == name))
+				.max_by_key(|package| package.priority)?,
+		};
+
+		package.resources.iter().find(|res| res.name_str() == name)
+	}
+
+	// None for evicted data, see `restore_resource_data`
+	pub fn get_resource_data(&self, name: &str) -> Option<&Vec<u8>> {
+		self.get_package_resource_data(self.layers.get(name)?, name)
+	}
+
+	// data of a shadowed resource is still available from its own package
+	pub fn get_package_resource_data(&self, package: &str, name: &str) -> Option<&Vec<u8>> {
+		let resource = self.get_layer(package)?.resources.get(name)?;
+		let tick = self.tick.fetch_add(1, Ordering::Relaxed) + 1;
+		resource.last_used.store(tick, Ordering::Relaxed);
+		resource.data.as_ref()
+	}
+
+	pub fn is_resource_resident(&self, name: &str) -> bool {
+		self.layers
+			.get(name)
+			.and_then(|layer| self.get_layer(layer))
+			.and_then(|layer| layer.resources.get(name))
+			.map_or(false, |resource| resource.data.is_some())
+	}
+
+	// reads evicted data again from source files or the package archive
+	pub fn restore_resource_data(&mut self, name: &str) -> Result<(), String> {
+		let layer = self
+			.layers
+			.get(name)
+			.ok_or(format!("resource '{}' is not loaded", name))?
+			.clone();
+
+		if self.is_resource_resident(name) {
+			return Ok(());
+		}
+
+		let source = self.get_layer(&layer).map(|layer| layer.source.clone());
+		let data = match source {
+			// not verified, the file may be edited since it was accepted by `poll_changes`
+			Some(LayerSource::Directory) => {
+				let (_, path) = self
+					.get_package_files(&layer)?
+					.into_iter()
+					.find(|(res, _)| res.name_str() == name)
+					.ok_or(format!("resource '{}' not exists", name))?;
+
+				std::fs::read(&path).or(Err(format!("cant read file: {}", path.to_string_lossy())))?
+			}
+			Some(LayerSource::Archive(path)) => {
+				let data = std::fs::read(&path).or(Err(format!("cant read file: {}", path.to_string_lossy())))?;
+				read_package_entry(self.get_package(&layer)?, &data, name)?
+			}
+			_ => return Err(format!("resource '{}' can't be restored", name)),
+		};
+
+		let tick = self.tick.fetch_add(1, Ordering::Relaxed) + 1;
+		let resource = self
+			.data_loaded
+			.iter_mut()
+			.find(|e| e.name == layer)
+			.and_then(|e| e.resources.get_mut(name))
+			.ok_or(format!("resource '{}' is not loaded", name))?;
+		resource.data = Some(data);
+		resource.last_used.store(tick, Ordering::Relaxed);
+		Ok(())
+	}
+
+	// resident bytes of all loaded packages
+	pub fn memory_usage(&self) -> usize {
+		self.data_loaded.iter().map(|layer| layer.memory_usage()).sum()
+	}
+
+	pub fn package_memory_usage(&self, package: &str) -> usize {
+		self.get_layer(package).map_or(0, |layer| layer.memory_usage())
+	}
+
+	// evicts least recently used data of packages read from disk or archives until `memory_usage() <= budget`,
+	// shadowed resources go first, returns freed bytes
+	pub fn trim(&mut self, budget: usize) -> usize {
+		let mut usage = self.memory_usage();
+		if usage <= budget {
+			return 0;
+		}
+
+		let mut candidates: Vec<(bool, usize, usize, String)> = Vec::new();
+		for (i, layer) in self.data_loaded.iter().enumerate().filter(|(_, layer)| layer.source != LayerSource::Memory) {
+			for (name, resource) in layer.resources.iter().filter(|(_, res)| res.data.is_some()) {
+				let served = self.layers.get(name).map_or(false, |served| *served == layer.name);
+				let last_used = resource.last_used.load(Ordering::Relaxed);
+				candidates.push((served, last_used, i, name.clone()));
+			}
+		}
+		candidates.sort();
+
+		let mut freed = 0;
+		for (_, _, i, name) in candidates {
+			if usage <= budget {
+				break;
+			}
+			if let Some(data) = self.data_loaded[i].resources.get_mut(&name).and_then(|res| res.data.take()) {
+				usage -= data.len();
+				freed += data.len();
+			}
+		}
+
+		freed
+	}
+
+	// the package the resource is served from
+	pub fn get_resource_layer(&self, name: &str) -> Option<&str> {
+		self.layers.get(name).map(|layer| layer.as_str())
+	}
+
+	// loaded packages from lowest to highest priority
+	pub fn get_layers(&self) -> Vec<&str> {
+		let mut layers: Vec<_> = self
+			.data_loaded
+			.iter()
+			.map(|layer| (self.get_priority(&layer.name), layer.name.as_str()))
+			.collect();
+		layers.sort_by_key(|(priority, _)| *priority);
+		layers.into_iter().map(|(_, name)| name).collect()
+	}
+
+	pub fn is_package_loaded(&self, name: &str) -> bool {
+		self.get_layer(name).is_some()
+	}
+
+	pub fn register_reader(&mut self) -> ReaderId<LayerEvent> {
+		self.events.register_reader()
+	}
+
+	pub fn read_events<'a>(&'a self, reader: &mut ReaderId<LayerEvent>) -> EventIterator<'a, LayerEvent> {
+		self.events.read(reader)
+	}
+
+	// package stays in the package list and can be loaded again
+	pub fn unload_package(&mut self, name: &str) {
+		if self.is_package_loaded(name) {
+			self.set_layer(name, None, LayerSource::Memory);
+		}
+	}
+
+	pub fn remove_package(&mut self, name: &str) -> Result<(), String> {
+		if self
+			.get_package_list()?
+			.packages
+			.iter()
+			.all(|package| package.name != name)
+		{
+			return Err(format!("package '{}' not exists", name));
+		}
+
+		self.unload_package(name);
+		self.package_dirs.remove(name);
+		self.get_package_list_mut()?.packages.retain(|package| package.name != name);
+		Ok(())
+	}
+
+	pub fn load_package_from_disk(&mut self, name: &str) -> Result<(), String> {
+		let mut resources = HashMap::new();
+		let mut modified = Vec::new();
+
+		for (res, path) in self.get_package_files(name)? {
+			let buffer = std::fs::read(&path)
+				.or(Err(format!("cant read file: {}", path.to_string_lossy())))?;
+
+			res.verify_source(&buffer)?;
+			modified.push((res.name_str(), modified_time(&path)));
+			resources.insert(res.name_str(), buffer);
+		}
+
+		self.set_layer(name, Some(resources), LayerSource::Directory);
+
+		if let Some(layer) = self.data_loaded.iter_mut().find(|layer| layer.name == name) {
+			for (resource, time) in modified {
+				layer.resources.get_mut(&resource).unwrap().modified = time;
+			}
+		}
+		Ok(())
+	}
+
+	// re-reads changed source files of packages loaded from disk, changes of served resources
+	// are sent as `LayerEvent::Changed`, manifest is not checked for edited files
+	pub fn poll_changes(&mut self) -> Vec<String> {
+		let packages: Vec<String> = self
+			.data_loaded
+			.iter()
+			.filter(|layer| layer.source == LayerSource::Directory)
+			.map(|layer| layer.name.clone())
+			.collect();
+
+		let mut changed = Vec::new();
+		for package in packages {
+			let files = match self.get_package_files(&package) {
+				Ok(files) => files,
+				Err(_) => continue,
+			};
+			let layer = self.data_loaded.iter_mut().find(|layer| layer.name == package).unwrap();
+
+			for (res, path) in files {
+				let name = res.name_str();
+				let resource = match layer.resources.get_mut(&name) {
+					Some(resource) => resource,
+					None => continue,
+				};
+				// file can be missing while it is saved, it is checked again on next poll
+				let time = match modified_time(&path) {
+					Some(time) => time,
+					None => continue,
+				};
+
+				match resource.modified {
+					Some(modified) if modified != time => {}
+					Some(_) => continue,
+					None => {
+						resource.modified = Some(time);
+						continue;
+					}
+				}
+
+				// evicted data is read on demand
+				if resource.data.is_some() {
+					match std::fs::read(&path) {
+						Ok(data) => resource.data = Some(data),
+						Err(_) => continue,
+					}
+				}
+				resource.modified = Some(time);
+
+				if self.layers.get(&name) == Some(&package) {
+					changed.push(name);
+				}
+			}
+		}
+
+		if !changed.is_empty() {
+			changed.sort();
+			self.events.single_write(LayerEvent::Changed(changed.clone()));
+		}
+		changed
+	}
+
+	pub fn load_all_packages(&mut self) -> Result<(), String> {
+		let package_names: Vec<String> = {
+			self.get_package_list()?
+				.packages
+				.iter()
+				.map(|package| package.name.clone())
+				.collect()
+		};
+
+		for package in package_names {
+			self.load_package(&package)?;
+		}
+
+		Ok(())
+	}
+
+	pub fn load_package_async(&self, loader: &BackgroundLoader, name: &str) -> Result<LoadHandle<PackageData>, String> {
+		let mode = self.mode;
+
+		if mode == LoadMode::Archive {
+			let path = self.get_package_archive_path(name)?;
+			let package = self.get_package(name)?.clone();
+
+			return Ok(loader.spawn(move || {
+				let data = std::fs::read(&path).or(Err(format!("cant read file: {}", path.to_string_lossy())))?;
+				let resources = read_package(&package, &data)?.into_iter().collect();
+				Ok(PackageData {
+					package: package.name,
+					mode,
+					resources,
+				})
+			}));
+		}
+
+		let files = self.get_package_files(name)?;
+		let package = name.to_string();
+
+		Ok(loader.spawn(move || {
+			let resources = files
+				.par_iter()
+				.map(|(res, path)| {
+					let data = std::fs::read(path).or(Err(format!("cant read file: {}", path.to_string_lossy())))?;
+					res.verify_source(&data)?;
+					Ok((res.name_str(), data))
+				})
+				.collect::<Result<_, String>>()?;
+
+			Ok(PackageData { package, mode, resources })
+		}))
+	}
+
+	pub fn load_all_packages_async(&self, loader: &BackgroundLoader) -> Result<Vec<LoadHandle<PackageData>>, String> {
+		self.get_package_list()?
+			.packages
+			.iter()
+			.map(|package| self.load_package_async(loader, &package.name))
+			.collect()
+	}
+
+	pub fn add_package_resources(&mut self, data: PackageData) {
+		let source = match data.mode {
+			LoadMode::Directory => LayerSource::Directory,
+			LoadMode::Archive => self
+				.get_package_archive_path(&data.package)
+				.map_or(LayerSource::Memory, LayerSource::Archive),
+		};
+		self.set_layer(&data.package, Some(data.resources.into_iter().collect()), source);
+	}
+
+	pub fn load_all(&mut self, path: &PathBuf) -> Result<(), String> {
+		self.load_package_list(path)?;
+		self.load_all_packages()
+	}
+
+	fn get_package(&self, name: &str) -> Result<&Package, String> {
+		self.get_package_list()?
+			.packages
+			.iter()
+			.find(|package| package.name == name)
+			.ok_or(format!("package '{}' not exists", name))
+	}
+
+	fn get_package_dir(&self, name: &str) -> Result<PathBuf, String> {
+		match self.package_dirs.get(name) {
+			Some(dir) => Ok(dir.clone()),
+			None => {
+				let mut dir = self
+					.package_list_path
+					.clone()
+					.ok_or("package list path is not specified")?;
+				dir.pop();
+				Ok(dir)
+			}
+		}
+	}
+
+	fn get_package_archive_path(&self, name: &str) -> Result<PathBuf, String> {
+		let package = self.get_package(name)?;
+		Ok(self.get_package_dir(name)?.join(package.file_name()))
+	}
+
+	fn get_package_files(&self, name: &str) -> Result<Vec<(Resource, PathBuf)>, String> {
+		let package = self.get_package(name)?;
+		let dir = self.get_package_dir(name)?;
+
+		Ok(package
+			.resources
+			.iter()
+			.map(|res| (res.clone(), dir.join(&res.path)))
+			.collect())
+	}
+
+	fn get_package_list_mut(&mut self) -> Result<&mut PackageList, String> {
+		Ok(self.package_list.as_mut().ok_or("package_list is None")?)
+	}
+
+	fn get_priority(&self, name: &str) -> i32 {
+		self.package_list
+			.as_ref()
+			.and_then(|list| list.packages.iter().find(|package| package.name == name))
+			.map_or(0, |package| package.priority)
+	}
+
+	fn get_layer(&self, name: &str) -> Option<&Layer> {
+		self.data_loaded.iter().find(|layer| layer.name == name)
+	}
+
+	// replaces the data of a layer, None unloads it
+	fn set_layer(&mut self, name: &str, data: Option<HashMap<String, Vec<u8>>>, source: LayerSource) {
+		let reloaded = match self.data_loaded.iter().position(|layer| layer.name == name) {
+			Some(i) => {
+				self.data_loaded.remove(i);
+				true
+			}
+			None => false,
+		};
+		let loaded = data.is_some();
+		if let Some(data) = data {
+			let tick = self.tick.load(Ordering::Relaxed);
+			let resources = data
+				.into_iter()
+				.map(|(name, data)| {
+					let last_used = AtomicUsize::new(tick);
+					(name, ResourceData { data: Some(data), last_used, modified: None })
+				})
+				.collect();
+
+			self.data_loaded.push(Layer {
+				name: name.to_string(),
+				source,
+				resources,
+			});
+		}
+
+		let mut layers: HashMap<String, (i32, String)> = HashMap::new();
+		for layer in self.data_loaded.iter() {
+			let priority = self.get_priority(&layer.name);
+			for resource in layer.resources.keys() {
+				if layers.get(resource).map_or(true, |(current, _)| *current <= priority) {
+					layers.insert(resource.clone(), (priority, layer.name.clone()));
+				}
+			}
+		}
+		let layers: HashMap<String, String> = layers.into_iter().map(|(k, (_, layer))| (k, layer)).collect();
+
+		let mut changed: Vec<String> = self
+			.layers
+			.keys()
+			.chain(layers.keys())
+			.filter(|resource| {
+				let old = self.layers.get(*resource);
+				let new = layers.get(*resource);
+				old != new || (reloaded && new.map_or(false, |layer| layer == name))
+			})
+			.cloned()
+			.collect();
+		changed.sort();
+		changed.dedup();
+
+		self.layers = layers;
+
+		match (reloaded, loaded) {
+			(false, true) => self.events.single_write(LayerEvent::Added(name.to_string())),
+			(true, false) => self.events.single_write(LayerEvent::Removed(name.to_string())),
+			_ => {}
+		}
+		if !changed.is_empty() {
+			self.events.single_write(LayerEvent::Changed(changed));
+		}
+	}
+}
+
+impl Default for LoadMode {
+	#[cfg(not(feature = "prod"))]
+	fn default() -> Self {
+		LoadMode::Directory
+	}
+
+	#[cfg(feature = "prod")]
+	fn default() -> Self {
+		LoadMode::Archive
+	}
+}
+
+impl Layer {
+	fn memory_usage(&self) -> usize {
+		self.resources
+			.values()
+			.filter_map(|res| res.data.as_ref())
+			.map(|data| data.len())
+			.sum()
+	}
+}
+
+pub fn data_type_from_path(path: &Path) -> &'static str {
+	let extension = path
+		.extension()
+		.map_or(String::new(), |e| e.to_string_lossy().to_lowercase());
+
+	match extension.as_str() {
+		"png" | "jpg" | "jpeg" | "bmp" | "tga" | "gif" | "hdr" => "texture",
+		"glsl" | "vert" | "frag" | "geom" => "shader",
+		"gltf" | "glb" => "gltf",
+		"obj" => "obj",
+		"ttf" | "otf" => "font",
+		"scene" => "scene",
+		_ => "raw",
+	}
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+	std::fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+fn collect_resources(root: &Path, dir: &Path, res: &mut Vec<Resource>) -> Result<(), String> {
+	let entries = std::fs::read_dir(dir).or(Err(format!("cant read dir: {}", dir.to_string_lossy())))?;
+
+	for entry in entries {
+		let path = entry.or_else(|e| Err(format!("{}", e)))?.path();
+		if path.is_dir() {
+			collect_resources(root, &path, res)?;
+			continue;
+		}
+
+		let name = path.strip_prefix(root).or(Err("wrong resource path"))?.to_path_buf();
+		res.push(Resource {
+			data_type: data_type_from_path(&path).to_string(),
+			path: name.clone(),
+			name,
+			size: None,
+			hash: None,
+			source_hash: None,
+		});
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn test_dir(name: &str) -> PathBuf {
+		let dir = std::env::temp_dir().join(format!("resource-manager-{}-{}", std::process::id(), name));
+		let _ = std::fs::remove_dir_all(&dir);
+		std::fs::create_dir_all(&dir).unwrap();
+		dir
+	}
+
+	fn resource(name: &str, path: PathBuf) -> Resource {
+		Resource {
+			name: PathBuf::from(name),
+			data_type: "text".to_string(),
+			path,
+			size: None,
+			hash: None,
+			source_hash: None,
+		}
+	}
+
+	// writes two resource files and returns a package with absolute paths to them
+	fn create_package(dir: &PathBuf, compression: Compression) -> Package {
+		std::fs::write(dir.join("a.txt"), b"first resource").unwrap();
+		std::fs::create_dir_all(dir.join("sub")).unwrap();
+		std::fs::write(dir.join("sub/b.txt"), vec![7u8; 4096]).unwrap();
+
+		Package {
+			name: "test".to_string(),
+			priority: 0,
+			compression: Some(compression),
+			textures: None,
+			meshes: None,
+			resources: vec![
+				resource("a.txt", dir.join("a.txt")),
+				resource("sub/b.txt", dir.join("sub/b.txt")),
+			],
+		}
+	}
+
+	fn manager_with(package: Package) -> ResourceManager {
+		let mut manager = ResourceManager::new();
+		manager.set_package_list(PackageList {
+			packages: vec![package],
+		});
+		manager
+	}
+
+	fn check_data(manager: &ResourceManager) {
+		assert_eq!(manager.get_resource_data("a.txt").unwrap(), &b"first resource".to_vec());
+		assert_eq!(manager.get_resource_data("sub/b.txt").unwrap(), &vec![7u8; 4096]);
+	}
+
+	#[test]
+	fn load_package_from_tar_bytes() {
+		for (i, compression) in [Compression::None, Compression::Gzip, Compression::Zstd].iter().enumerate() {
+			let dir = test_dir(&format!("tar-{}", i));
+			let mut package = create_package(&dir, *compression);
+
+			let data = build_package_data(&mut package).unwrap();
+			assert_eq!(package.resources[0].size, Some(14));
+
+			let mut manager = manager_with(package);
+			manager.add_package_data(&data, "test").unwrap();
+			check_data(&manager);
+		}
+	}
+
+	#[test]
+	fn load_package_from_disk() {
+		let dir = test_dir("disk");
+		let mut package = create_package(&dir, Compression::None);
+		build_package_data(&mut package).unwrap();
+
+		package.resources[0].path = PathBuf::from("a.txt");
+		package.resources[1].path = PathBuf::from("sub/b.txt");
+
+		let list_path = dir.join("res.json");
+		let list = PackageList {
+			packages: vec![package],
+		};
+		std::fs::write(&list_path, serde_json::to_string(&list).unwrap()).unwrap();
+
+		let mut manager = ResourceManager::new();
+		manager.load_all(&list_path).unwrap();
+		check_data(&manager);
+	}
+
+	#[test]
+	fn report_corrupted_resource() {
+		let dir = test_dir("corrupted");
+		let mut package = create_package(&dir, Compression::None);
+		build_package_data(&mut package).unwrap();
+
+		std::fs::write(dir.join("a.txt"), b"first resourcE").unwrap();
+		let list_path = dir.join("res.json");
+		std::fs::write(&list_path, serde_json::to_string(&PackageList { packages: vec![package] }).unwrap()).unwrap();
+
+		let mut manager = ResourceManager::new();
+		let err = manager.load_all(&list_path).unwrap_err();
+		assert!(err.contains("corrupted"), "{}", err);
+	}
+
+	#[test]
+	fn load_baked_package_from_disk() {
+		let dir = test_dir("baked");
+		let mut package = create_package(&dir, Compression::None);
+		std::fs::write(dir.join("quad.obj"), b"mtllib quad.mtl\nv 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nusemtl stone\nf 1 2 3\nf 1 3 4\n").unwrap();
+		std::fs::write(dir.join("quad.mtl"), b"newmtl stone\nmap_bump stone_normal.png\n").unwrap();
+		let mut quad = resource("quad.obj", dir.join("quad.obj"));
+		quad.data_type = "obj".to_string();
+		package.resources.push(quad);
+		package.meshes = Some(MeshBake::default());
+
+		let data = build_package_data(&mut package).unwrap();
+		assert!(package.resources[2].source_hash.is_some());
+		assert!(package.resources[0].source_hash.is_none());
+
+		let list_path = dir.join("res.json");
+		std::fs::write(dir.join(package.file_name()), data).unwrap();
+		std::fs::write(&list_path, serde_json::to_string(&PackageList { packages: vec![package] }).unwrap()).unwrap();
+
+		for mode in [LoadMode::Directory, LoadMode::Archive].iter() {
+			let mut manager = ResourceManager::new();
+			manager.set_mode(*mode);
+			manager.load_all(&list_path).unwrap();
+			check_data(&manager);
+
+			let quad = String::from_utf8(manager.get_resource_data("quad.obj").unwrap().clone()).unwrap();
+			assert!(quad.contains("mtllib quad.mtl") && quad.contains("usemtl stone"));
+		}
+	}
+
+	#[test]
+	fn report_truncated_archive() {
+		for (i, compression) in [Compression::None, Compression::Gzip, Compression::Zstd].iter().enumerate() {
+			let dir = test_dir(&format!("truncated-{}", i));
+			let mut package = create_package(&dir, *compression);
+
+			let data = build_package_data(&mut package).unwrap();
+			let mut manager = manager_with(package);
+
+			assert!(manager.add_package_data(&data[..data.len() / 2], "test").is_err());
+		}
+	}
+
+	#[test]
+	fn layered_packages() {
+		let base = test_dir("layer-base");
+		std::fs::write(base.join("a.txt"), b"base").unwrap();
+		std::fs::write(base.join("b.txt"), b"base b").unwrap();
+		let dev = test_dir("layer-dev");
+		std::fs::write(dev.join("a.txt"), b"dev").unwrap();
+
+		let mut manager = ResourceManager::new();
+		manager.mount_directory("base", &base, 0).unwrap();
+		let mut reader = manager.register_reader();
+
+		manager.mount_directory("dev", &dev, 10).unwrap();
+		assert_eq!(manager.get_resource_data("a.txt").unwrap(), &b"dev".to_vec());
+		assert_eq!(manager.get_resource_layer("a.txt"), Some("dev"));
+		assert_eq!(manager.get_resource_layer("b.txt"), Some("base"));
+		assert_eq!(manager.get_layers(), vec!["base", "dev"]);
+		assert_eq!(
+			manager.read_events(&mut reader).cloned().collect::<Vec<_>>(),
+			vec![LayerEvent::Added("dev".to_string()), LayerEvent::Changed(vec!["a.txt".to_string()])]
+		);
+
+		manager.unload_package("dev");
+		assert_eq!(manager.get_resource_data("a.txt").unwrap(), &b"base".to_vec());
+		assert_eq!(manager.get_resource_layer("a.txt"), Some("base"));
+		assert_eq!(
+			manager.read_events(&mut reader).cloned().collect::<Vec<_>>(),
+			vec![LayerEvent::Removed("dev".to_string()), LayerEvent::Changed(vec!["a.txt".to_string()])]
+		);
+	}
+
+	#[test]
+	fn evict_least_recently_used() {
+		let dir = test_dir("evict");
+		let package = create_package(&dir, Compression::None);
+
+		let list_path = dir.join("res.json");
+		std::fs::write(&list_path, serde_json::to_string(&PackageList { packages: vec![package.clone()] }).unwrap()).unwrap();
+
+		let mut manager = ResourceManager::new();
+		manager.load_all(&list_path).unwrap();
+		assert_eq!(manager.memory_usage(), 14 + 4096);
+
+		manager.get_resource_data("sub/b.txt").unwrap();
+		manager.get_resource_data("a.txt").unwrap();
+		assert_eq!(manager.trim(1000), 4096);
+		assert!(!manager.is_resource_resident("sub/b.txt"));
+		assert!(manager.get_resource_data("sub/b.txt").is_none());
+		assert_eq!(manager.package_memory_usage("test"), 14);
+
+		manager.restore_resource_data("sub/b.txt").unwrap();
+		check_data(&manager);
+
+		// data passed from memory can't be evicted
+		let mut package = package;
+		let data = build_package_data(&mut package).unwrap();
+		let mut manager = manager_with(package);
+		manager.add_package_data(&data, "test").unwrap();
+		assert_eq!(manager.trim(0), 0);
+	}
+
+	#[test]
+	fn restore_edited_resource() {
+		let dir = test_dir("evict-edited");
+		let mut package = create_package(&dir, Compression::None);
+		build_package_data(&mut package).unwrap();
+
+		let list_path = dir.join("res.json");
+		std::fs::write(&list_path, serde_json::to_string(&PackageList { packages: vec![package] }).unwrap()).unwrap();
+
+		let mut manager = ResourceManager::new();
+		manager.set_mode(LoadMode::Directory);
+		manager.load_all(&list_path).unwrap();
+
+		std::thread::sleep(std::time::Duration::from_millis(20));
+		std::fs::write(dir.join("a.txt"), b"edited").unwrap();
+		assert_eq!(manager.poll_changes(), vec!["a.txt".to_string()]);
+
+		manager.trim(0);
+		manager.restore_resource_data("a.txt").unwrap();
+		assert_eq!(manager.get_resource_data("a.txt").unwrap(), &b"edited".to_vec());
+	}
+
+	#[test]
+	fn evict_and_restore_from_archive() {
+		let dir = test_dir("evict-archive");
+		let mut package = create_package(&dir, Compression::Zstd);
+		let data = build_package_data(&mut package).unwrap();
+		std::fs::write(dir.join(package.file_name()), data).unwrap();
+
+		let list_path = dir.join("res.json");
+		std::fs::write(&list_path, serde_json::to_string(&PackageList { packages: vec![package] }).unwrap()).unwrap();
+
+		let mut manager = ResourceManager::new();
+		manager.set_mode(LoadMode::Archive);
+		manager.load_all(&list_path).unwrap();
+
+		manager.get_resource_data("sub/b.txt").unwrap();
+		manager.get_resource_data("a.txt").unwrap();
+		assert_eq!(manager.trim(1000), 4096);
+		assert!(!manager.is_resource_resident("sub/b.txt"));
+
+		manager.restore_resource_data("sub/b.txt").unwrap();
+		check_data(&manager);
+		assert_eq!(manager.trim(0), 14 + 4096);
+	}
+
+	#[test]
+	fn directory_and_archive_mode() {
+		let dir = test_dir("modes");
+		let mut package = create_package(&dir, Compression::Gzip);
+		package.resources[0].path = PathBuf::from("a.txt");
+		package.resources[1].path = PathBuf::from("sub/b.txt");
+
+		let list_path = dir.join("res.json");
+		let list = PackageList {
+			packages: vec![package.clone()],
+		};
+		std::fs::write(&list_path, serde_json::to_string(&list).unwrap()).unwrap();
+
+		let mut manager = ResourceManager::new();
+		manager.set_mode(LoadMode::Directory);
+		manager.load_all(&list_path).unwrap();
+		let mut reader = manager.register_reader();
+		assert!(manager.poll_changes().is_empty());
+
+		std::thread::sleep(std::time::Duration::from_millis(20));
+		std::fs::write(dir.join("a.txt"), b"changed").unwrap();
+		assert_eq!(manager.poll_changes(), vec!["a.txt".to_string()]);
+		assert_eq!(manager.get_resource_data("a.txt").unwrap(), &b"changed".to_vec());
+		assert_eq!(
+			manager.read_events(&mut reader).cloned().collect::<Vec<_>>(),
+			vec![LayerEvent::Changed(vec!["a.txt".to_string()])]
+		);
+
+		// the same list is served from the archive
+		std::fs::write(dir.join("a.txt"), b"first resource").unwrap();
+		package.resources[0].path = dir.join("a.txt");
+		package.resources[1].path = dir.join("sub/b.txt");
+		let data = build_package_data(&mut package).unwrap();
+		std::fs::write(dir.join(package.file_name()), data).unwrap();
+		std::fs::remove_file(dir.join("a.txt")).unwrap();
+
+		let mut manager = ResourceManager::new();
+		manager.set_mode(LoadMode::Archive);
+		manager.load_all(&list_path).unwrap();
+		check_data(&manager);
+	}
+}
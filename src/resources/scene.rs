@@ -0,0 +1,470 @@
+extern crate serde;
+extern crate serde_json;
+extern crate specs;
+
+use self::serde::{Deserialize, Serialize};
+use self::specs::prelude::*;
+use super::AssetManager;
+use core::{
+	Blending, Children, DirectionalLight, EntityRelations, Material, PerspectiveCamera, PointLight, ShaderSource,
+	ShaderTag, SharedGeometry, SharedMaterials, SharedTexture2D, Transform, Uniform, UniformName,
+};
+use math::{Euler, Matrix3, Matrix4, Vector2, Vector3, Vector4};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+// Level data, geometries and textures are referenced by resource name
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Scene {
+	pub entities: Vec<SceneEntity>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct SceneEntity {
+	pub transform: SceneTransform,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub camera: Option<SceneCamera>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub point_light: Option<ScenePointLight>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub directional_light: Option<SceneDirectionalLight>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub geometry: Option<SceneGeometry>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub materials: Vec<SceneMaterial>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub children: Vec<SceneEntity>,
+}
+
+// rotation is euler angles in radians
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct SceneTransform {
+	pub position: [f32; 3],
+	pub rotation: [f32; 3],
+	pub scale: [f32; 3],
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct SceneCamera {
+	pub fov: f32,
+	pub zoom: f32,
+	pub near: f32,
+	pub far: f32,
+	pub focus: f32,
+	pub aspect: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ScenePointLight {
+	pub color: [f32; 3],
+	pub power: f32,
+	pub distance: f32,
+	pub decay: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct SceneDirectionalLight {
+	pub color: [f32; 3],
+	pub direction: [f32; 3],
+	pub power: f32,
+}
+
+// mesh `index` of an obj resource
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SceneGeometry {
+	pub resource: String,
+	#[serde(default)]
+	pub index: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SceneMaterial {
+	pub shader: String,
+	#[serde(default, skip_serializing_if = "String::is_empty")]
+	pub name: String,
+	#[serde(default)]
+	pub blending: Blending,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub tags: Vec<ShaderTag>,
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub defines: BTreeMap<String, String>,
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub uniforms: BTreeMap<String, SceneUniform>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SceneUniform {
+	Float(f32),
+	Int(i32),
+	#[serde(rename = "uint")]
+	UInt(u32),
+	Bool(bool),
+	Vec2([f32; 2]),
+	Vec3([f32; 3]),
+	Vec4([f32; 4]),
+	Mat3([f32; 9]),
+	Mat4([f32; 16]),
+	// texture resource name
+	Texture(Option<String>),
+}
+
+impl Default for SceneTransform {
+	fn default() -> Self {
+		Self {
+			position: [0.0; 3],
+			rotation: [0.0; 3],
+			scale: [1.0; 3],
+		}
+	}
+}
+
+impl Default for SceneCamera {
+	fn default() -> Self {
+		Self::from(&PerspectiveCamera::new())
+	}
+}
+
+impl Default for ScenePointLight {
+	fn default() -> Self {
+		Self::from(&PointLight::default())
+	}
+}
+
+impl Default for SceneDirectionalLight {
+	fn default() -> Self {
+		Self::from(&DirectionalLight::default())
+	}
+}
+
+impl Scene {
+	pub fn from_slice(data: &[u8]) -> Result<Self, String> {
+		serde_json::from_slice(data).or_else(|e| Err(format!("{}", e)))
+	}
+
+	pub fn to_json(&self) -> String {
+		serde_json::to_string_pretty(self).unwrap()
+	}
+
+	pub fn read(path: &Path) -> Result<Self, String> {
+		let data = std::fs::read(path).or(Err(format!("cant read scene: {}", path.to_string_lossy())))?;
+		Self::from_slice(&data)
+	}
+
+	pub fn write(&self, path: &Path) -> Result<(), String> {
+		std::fs::write(path, self.to_json()).or(Err(format!("cant write scene: {}", path.to_string_lossy())))
+	}
+}
+
+impl<'a> From<&'a Transform> for SceneTransform {
+	fn from(transform: &Transform) -> Self {
+		Self {
+			position: to_array3(&transform.position),
+			rotation: [transform.rotation.x, transform.rotation.y, transform.rotation.z],
+			scale: to_array3(&transform.scale),
+		}
+	}
+}
+
+impl SceneTransform {
+	pub fn to_transform(&self) -> Transform {
+		let mut transform = Transform::from_position(from_array3(&self.position));
+		let mut rotation = Euler::default();
+		rotation.set(self.rotation[0], self.rotation[1], self.rotation[2]);
+		transform.rotation = rotation;
+		transform.scale = from_array3(&self.scale);
+		transform.update();
+		transform
+	}
+}
+
+impl<'a> From<&'a PerspectiveCamera> for SceneCamera {
+	fn from(camera: &PerspectiveCamera) -> Self {
+		Self {
+			fov: camera.fov,
+			zoom: camera.zoom,
+			near: camera.near,
+			far: camera.far,
+			focus: camera.focus,
+			aspect: camera.aspect,
+		}
+	}
+}
+
+impl SceneCamera {
+	pub fn to_camera(&self) -> PerspectiveCamera {
+		let mut camera = PerspectiveCamera::new();
+		camera.fov = self.fov;
+		camera.zoom = self.zoom;
+		camera.near = self.near;
+		camera.far = self.far;
+		camera.focus = self.focus;
+		camera.aspect = self.aspect;
+		camera.update_projection_matrix();
+		camera
+	}
+}
+
+impl<'a> From<&'a PointLight> for ScenePointLight {
+	fn from(light: &PointLight) -> Self {
+		Self {
+			color: to_array3(&light.color),
+			power: light.power,
+			distance: light.distance,
+			decay: light.decay,
+		}
+	}
+}
+
+impl ScenePointLight {
+	pub fn to_light(&self) -> PointLight {
+		PointLight::new(from_array3(&self.color), self.power, self.distance, self.decay)
+	}
+}
+
+impl<'a> From<&'a DirectionalLight> for SceneDirectionalLight {
+	fn from(light: &DirectionalLight) -> Self {
+		Self {
+			color: to_array3(&light.color),
+			direction: to_array3(&light.direction),
+			power: light.power,
+		}
+	}
+}
+
+impl SceneDirectionalLight {
+	pub fn to_light(&self) -> DirectionalLight {
+		DirectionalLight::new(from_array3(&self.color), from_array3(&self.direction), self.power)
+	}
+}
+
+impl SceneMaterial {
+	pub fn from_material(material: &Material, assets: &AssetManager) -> Result<Self, String> {
+		let program = material.get_shader_program().unwrap();
+		let shader = match program.get_source() {
+			ShaderSource::File(name) => name.clone(),
+			ShaderSource::Inline { .. } => return Err("inline shader materials can't be saved".to_string()),
+		};
+
+		let mut uniforms = BTreeMap::new();
+		for (name, uniform) in material.uniforms.iter() {
+			let uniform = SceneUniform::from_uniform(uniform, assets)
+				.ok_or(format!("uniform '{}' can't be saved", name.get_name()))?;
+			uniforms.insert(name.get_name(), uniform);
+		}
+
+		Ok(Self {
+			shader,
+			name: material.name.clone(),
+			blending: material.blending,
+			tags: program.get_tags().iter().cloned().collect(),
+			defines: program.get_defines().iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+			uniforms,
+		})
+	}
+
+	pub fn to_material(&self, assets: &mut AssetManager) -> Result<Material, String> {
+		let mut material = Material::new(&self.shader);
+		material.name = self.name.clone();
+		material.blending = self.blending;
+		for tag in self.tags.iter() {
+			material.add_tag(tag.clone());
+		}
+		for (name, value) in self.defines.iter() {
+			material.set_define(name, value);
+		}
+		for (name, uniform) in self.uniforms.iter() {
+			material.set_uniform(UniformName::from_name(name), uniform.to_uniform(assets)?);
+		}
+		Ok(material)
+	}
+}
+
+impl SceneUniform {
+	// arrays and structs have no scene representation
+	pub fn from_uniform(uniform: &Uniform, assets: &AssetManager) -> Option<Self> {
+		let uniform = match uniform {
+			Uniform::Float(v) => SceneUniform::Float(*v),
+			Uniform::Int(v) => SceneUniform::Int(*v),
+			Uniform::UInt(v) => SceneUniform::UInt(*v),
+			Uniform::Bool(v) => SceneUniform::Bool(*v),
+			Uniform::Vector2(v) => SceneUniform::Vec2([v.x, v.y]),
+			Uniform::Vector3(v) => SceneUniform::Vec3(to_array3(v)),
+			Uniform::Vector4(v) => SceneUniform::Vec4([v.x, v.y, v.z, v.w]),
+			Uniform::Matrix3(m) => SceneUniform::Mat3(m.elements),
+			Uniform::Matrix4(m) => SceneUniform::Mat4(m.elements),
+			Uniform::Texture2D(None, _) => SceneUniform::Texture(None),
+			Uniform::Texture2D(Some(texture), _) => {
+				SceneUniform::Texture(Some(assets.find_texture(texture)?.to_string()))
+			}
+			_ => return None,
+		};
+		Some(uniform)
+	}
+
+	pub fn to_uniform(&self, assets: &mut AssetManager) -> Result<Uniform, String> {
+		let uniform = match self {
+			SceneUniform::Float(v) => Uniform::Float(*v),
+			SceneUniform::Int(v) => Uniform::Int(*v),
+			SceneUniform::UInt(v) => Uniform::UInt(*v),
+			SceneUniform::Bool(v) => Uniform::Bool(*v),
+			SceneUniform::Vec2(v) => Uniform::Vector2(Vector2::new(v[0], v[1])),
+			SceneUniform::Vec3(v) => Uniform::Vector3(from_array3(v)),
+			SceneUniform::Vec4(v) => Uniform::Vector4(Vector4::new(v[0], v[1], v[2], v[3])),
+			SceneUniform::Mat3(elements) => Uniform::Matrix3(Matrix3 { elements: *elements }),
+			SceneUniform::Mat4(elements) => Uniform::Matrix4(Matrix4 { elements: *elements }),
+			SceneUniform::Texture(None) => Uniform::Texture2D(None, 0),
+			SceneUniform::Texture(Some(name)) => Uniform::Texture2D(Some(assets.get::<SharedTexture2D>(name)?), 0),
+		};
+		Ok(uniform)
+	}
+}
+
+// creates scene entities, returns the roots
+// on error entities created so far are removed
+pub fn load_scene(world: &mut World, assets: &mut AssetManager, scene: &Scene) -> Result<Vec<Entity>, String> {
+	let mut roots = Vec::with_capacity(scene.entities.len());
+	for data in scene.entities.iter() {
+		match load_entity(world, assets, data) {
+			Ok(entity) => roots.push(entity),
+			Err(err) => {
+				for root in roots {
+					world.remove_recursive(root).unwrap();
+				}
+				return Err(err);
+			}
+		}
+	}
+	Ok(roots)
+}
+
+// saves every entity with a transform, geometries and textures must be cached assets
+pub fn save_scene(world: &World, assets: &AssetManager) -> Result<Scene, String> {
+	let entities = world.entities();
+	let transforms = world.read_storage::<Transform>();
+	let children = world.read_storage::<Children>();
+
+	let child_set: HashSet<Entity> = (&children)
+		.join()
+		.flat_map(|e| e.children.iter().cloned())
+		.collect();
+
+	let roots: Vec<Entity> = (&entities, &transforms)
+		.join()
+		.map(|(entity, _)| entity)
+		.filter(|entity| !child_set.contains(entity))
+		.collect();
+
+	let mut scene = Scene::default();
+	for entity in roots {
+		scene.entities.push(save_entity(world, assets, entity)?);
+	}
+	Ok(scene)
+}
+
+fn load_entity(world: &mut World, assets: &mut AssetManager, data: &SceneEntity) -> Result<Entity, String> {
+	// assets are resolved before the entity is created
+	let geometry = match &data.geometry {
+		Some(geometry) => {
+			let geometries = assets.get::<Vec<SharedGeometry>>(&geometry.resource)?;
+			let geometry = geometries
+				.get(geometry.index)
+				.ok_or(format!("resource '{}' has no mesh {}", geometry.resource, geometry.index))?;
+			Some(geometry.clone())
+		}
+		None => None,
+	};
+	let materials = data
+		.materials
+		.iter()
+		.map(|material| material.to_material(assets))
+		.collect::<Result<Vec<_>, _>>()?;
+
+	let mut builder = world.create_entity().with(data.transform.to_transform());
+	if let Some(geometry) = geometry {
+		builder = builder.with(geometry);
+	}
+	if !materials.is_empty() {
+		builder = builder.with(SharedMaterials::new_collection(materials));
+	}
+	if let Some(camera) = &data.camera {
+		builder = builder.with(camera.to_camera());
+	}
+	if let Some(light) = &data.point_light {
+		builder = builder.with(light.to_light());
+	}
+	if let Some(light) = &data.directional_light {
+		builder = builder.with(light.to_light());
+	}
+	let entity = builder.build();
+
+	for child in data.children.iter() {
+		match load_entity(world, assets, child) {
+			Ok(child) => world.add_child(entity, child),
+			Err(err) => {
+				world.remove_recursive(entity).unwrap();
+				return Err(err);
+			}
+		}
+	}
+
+	Ok(entity)
+}
+
+fn save_entity(world: &World, assets: &AssetManager, entity: Entity) -> Result<SceneEntity, String> {
+	let mut data = SceneEntity::default();
+	let child_entities = {
+		let transforms = world.read_storage::<Transform>();
+		let cameras = world.read_storage::<PerspectiveCamera>();
+		let point_lights = world.read_storage::<PointLight>();
+		let directional_lights = world.read_storage::<DirectionalLight>();
+		let geometries = world.read_storage::<SharedGeometry>();
+		let materials = world.read_storage::<SharedMaterials>();
+		let children = world.read_storage::<Children>();
+
+		if let Some(transform) = transforms.get(entity) {
+			data.transform = SceneTransform::from(transform);
+		}
+		data.camera = cameras.get(entity).map(SceneCamera::from);
+		data.point_light = point_lights.get(entity).map(ScenePointLight::from);
+		data.directional_light = directional_lights.get(entity).map(SceneDirectionalLight::from);
+
+		if let Some(geometry) = geometries.get(entity) {
+			let (resource, index) = assets
+				.find_geometry(geometry)
+				.ok_or("geometry is not an asset and can't be saved".to_string())?;
+			data.geometry = Some(SceneGeometry {
+				resource: resource.to_string(),
+				index,
+			});
+		}
+
+		if let Some(materials) = materials.get(entity) {
+			for material in materials.iter() {
+				let material = material.lock().unwrap();
+				data.materials.push(SceneMaterial::from_material(&material, assets)?);
+			}
+		}
+
+		children.get(entity).map_or(Vec::new(), |e| e.children.clone())
+	};
+
+	for child in child_entities {
+		data.children.push(save_entity(world, assets, child)?);
+	}
+
+	Ok(data)
+}
+
+fn to_array3(v: &Vector3<f32>) -> [f32; 3] {
+	[v.x, v.y, v.z]
+}
+
+fn from_array3(v: &[f32; 3]) -> Vector3<f32> {
+	Vector3::new(v[0], v[1], v[2])
+}